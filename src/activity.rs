@@ -0,0 +1,119 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::serde::ts_seconds;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::MyFilteredProject;
+
+/// Commits in this many days before the analysis count as recent
+pub const RECENT_DAYS: i64 = 90;
+
+/// A repository without commits for this many days is abandoned
+pub const ABANDONED_DAYS: i64 = 365;
+
+/// What the history of the repository tells about its maintenance
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct RepositoryActivity {
+    #[serde(with = "ts_seconds")]
+    pub first_commit: DateTime<Utc>,
+    #[serde(with = "ts_seconds")]
+    pub last_commit: DateTime<Utc>,
+    /// Days from the first commit to the analysis
+    pub age_days: i64,
+    pub commits: u32,
+    /// Commits in the RECENT_DAYS before the analysis
+    pub recent_commits: u32,
+    /// Distinct author emails
+    pub authors: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActivityReport {
+    /// Number of projects with each status, see RepositoryActivity::status
+    pub statuses: HashMap<String, u32>,
+    pub single_author_count: u32,
+    pub abandoned_count: u32,
+    /// The longest abandoned first
+    pub abandoned: Vec<MyFilteredProject>,
+}
+
+impl RepositoryActivity {
+    /// From the output of `git log --format=%ct%x09%aE`, the commit timestamp and the author email
+    pub fn from_log(log: &str, now: DateTime<Utc>) -> Option<RepositoryActivity> {
+        let mut dates = vec![];
+        let mut authors = HashSet::new();
+        for line in log.lines() {
+            let Some((timestamp, email)) = line.split_once('\t') else {
+                continue;
+            };
+            let Some(date) = timestamp
+                .trim()
+                .parse()
+                .ok()
+                .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+            else {
+                continue;
+            };
+            dates.push(date);
+            authors.insert(email.trim().to_lowercase());
+        }
+
+        let first_commit = *dates.iter().min()?;
+        let last_commit = *dates.iter().max()?;
+        let recent = now - Duration::days(RECENT_DAYS);
+        Some(RepositoryActivity {
+            first_commit,
+            last_commit,
+            age_days: (now - first_commit).num_days(),
+            commits: dates.len() as u32,
+            recent_commits: dates.iter().filter(|date| **date >= recent).count() as u32,
+            authors: authors.len() as u32,
+        })
+    }
+
+    /// maintained: commits in the last RECENT_DAYS,
+    /// quiet: commits in the last ABANDONED_DAYS,
+    /// abandoned: no commits in ABANDONED_DAYS
+    pub fn status(&self, now: DateTime<Utc>) -> &'static str {
+        let idle_days = (now - self.last_commit).num_days();
+        if idle_days <= RECENT_DAYS {
+            "maintained"
+        } else if idle_days <= ABANDONED_DAYS {
+            "quiet"
+        } else {
+            "abandoned"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_log() {
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let day = 24 * 60 * 60;
+        let log = format!(
+            "{}\tAlice@example.org\n{}\tbob@example.org\n{}\talice@example.org\nnot a commit\n",
+            1_700_000_000 - 10 * day,
+            1_700_000_000 - 100 * day,
+            1_700_000_000 - 1000 * day,
+        );
+        let activity = RepositoryActivity::from_log(&log, now).unwrap();
+        assert_eq!(activity.commits, 3);
+        assert_eq!(activity.recent_commits, 1);
+        assert_eq!(activity.authors, 2);
+        assert_eq!(activity.age_days, 1000);
+        assert_eq!(
+            activity.last_commit,
+            DateTime::from_timestamp(1_700_000_000 - 10 * day, 0).unwrap()
+        );
+        assert_eq!(activity.status(now), "maintained");
+        assert_eq!(activity.status(now + Duration::days(200)), "quiet");
+        assert_eq!(activity.status(now + Duration::days(400)), "abandoned");
+
+        assert_eq!(RepositoryActivity::from_log("", now), None);
+    }
+}
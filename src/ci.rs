@@ -0,0 +1,136 @@
+use std::path::Path;
+
+/// The CI systems, the platforms where they run and the files configuring them.
+/// The systems without platforms work with any of them.
+/// A path ending with / is a folder that must contain at least one file.
+const CI_FILES: [(&str, &[&str], &[&str]); 15] = [
+    ("github-actions", &["github"], &[".github/workflows/"]),
+    ("gitlab-ci", &["gitlab"], &[".gitlab-ci.yml"]),
+    (
+        "forgejo-actions",
+        &["codeberg", "gitea"],
+        &[".forgejo/workflows/", ".gitea/workflows/"],
+    ),
+    (
+        "woodpecker",
+        &[],
+        &[".woodpecker/", ".woodpecker.yml", ".woodpecker.yaml"],
+    ),
+    (
+        "sourcehut-builds",
+        &["sourcehut"],
+        &[".build.yml", ".builds/"],
+    ),
+    (
+        "bitbucket-pipelines",
+        &["bitbucket"],
+        &["bitbucket-pipelines.yml"],
+    ),
+    ("travis", &[], &[".travis.yml", ".travis.yaml"]),
+    ("circleci", &[], &[".circleci/"]),
+    (
+        "azure-pipelines",
+        &[],
+        &[
+            "azure-pipelines.yml",
+            "azure-pipelines.yaml",
+            ".azure-pipelines/",
+        ],
+    ),
+    ("appveyor", &[], &["appveyor.yml", ".appveyor.yml"]),
+    ("jenkins", &[], &["Jenkinsfile"]),
+    ("buildkite", &[], &[".buildkite/"]),
+    // Not CI systems, but they run the same checks on every machine
+    ("tox", &[], &["tox.ini"]),
+    ("nox", &[], &["noxfile.py"]),
+    ("pre-commit", &[], &[".pre-commit-config.yaml"]),
+];
+
+/// The CI systems configured in the cloned repository that run on its platform.
+/// A local repository can be the mirror of any platform, all the systems are checked.
+pub fn ci_systems(platform: &str, path: &Path) -> Vec<String> {
+    CI_FILES
+        .iter()
+        .filter(|(_, platforms, _)| {
+            platform == "local" || platforms.is_empty() || platforms.contains(&platform)
+        })
+        .filter(|(_, _, files)| files.iter().any(|file| is_configured(path, file)))
+        .map(|(system, _, _)| system.to_string())
+        .collect()
+}
+
+fn is_configured(path: &Path, file: &str) -> bool {
+    match file.strip_suffix('/') {
+        Some(folder) => path
+            .join(folder)
+            .read_dir()
+            .is_ok_and(|mut entries| entries.any(|entry| entry.is_ok())),
+        None => path.join(file).is_file(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_ci_systems() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let path = temp_folder.path();
+        assert!(ci_systems("codeberg", path).is_empty());
+
+        fs::create_dir_all(path.join(".forgejo/workflows")).unwrap();
+        assert!(ci_systems("codeberg", path).is_empty());
+
+        fs::write(path.join(".forgejo/workflows/test.yml"), "on: push\n").unwrap();
+        fs::write(path.join(".woodpecker.yml"), "steps: []\n").unwrap();
+        fs::write(path.join(".build.yml"), "image: alpine/edge\n").unwrap();
+        assert_eq!(
+            ci_systems("codeberg", path),
+            vec!["forgejo-actions", "woodpecker"]
+        );
+        assert_eq!(
+            ci_systems("sourcehut", path),
+            vec!["woodpecker", "sourcehut-builds"]
+        );
+        assert_eq!(ci_systems("github", path), vec!["woodpecker"]);
+        assert_eq!(
+            ci_systems("local", path),
+            vec!["forgejo-actions", "woodpecker", "sourcehut-builds"]
+        );
+    }
+
+    #[test]
+    fn test_ci_systems_on_any_platform() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let path = temp_folder.path();
+        fs::create_dir_all(path.join(".circleci")).unwrap();
+        for file in [
+            ".travis.yml",
+            ".circleci/config.yml",
+            "azure-pipelines.yml",
+            "appveyor.yml",
+            "Jenkinsfile",
+            "tox.ini",
+            "noxfile.py",
+            ".pre-commit-config.yaml",
+        ] {
+            fs::write(path.join(file), "").unwrap();
+        }
+        assert_eq!(
+            ci_systems("github", path),
+            vec![
+                "travis",
+                "circleci",
+                "azure-pipelines",
+                "appveyor",
+                "jenkins",
+                "tox",
+                "nox",
+                "pre-commit"
+            ]
+        );
+        assert_eq!(ci_systems("gitlab", path).len(), 8);
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+use serde::Serialize;
+
+use crate::MyFilteredProject;
+
+// The list of classifiers accepted by PyPI, one per line.
+// Taken from https://pypi.org/classifiers/ (the trove-classifiers package).
+static KNOWN_CLASSIFIERS: LazyLock<HashSet<&str>> =
+    LazyLock::new(|| include_str!("classifiers.txt").lines().collect());
+
+/// Classifiers that PyPI no longer accepts, with the replacement if there is one
+const DEPRECATED_CLASSIFIERS: [(&str, Option<&str>); 4] = [
+    ("Environment :: Web Environment :: Mozilla", None),
+    ("Framework :: Opps", None),
+    (
+        "Natural Language :: Ukranian",
+        Some("Natural Language :: Ukrainian"),
+    ),
+    (
+        "Topic :: Communications :: Chat :: AOL Instant Messenger",
+        None,
+    ),
+];
+
+/// The categories we aggregate in the report
+pub const REPORTED_CATEGORIES: [&str; 4] = ["Development Status", "Framework", "Topic", "Typing"];
+
+const SEPARATOR: &str = " :: ";
+
+#[derive(Debug, Serialize)]
+pub struct ClassifierReport {
+    /// For each reported category the number of projects using each classifier in it
+    pub categories: HashMap<String, HashMap<String, u32>>,
+    pub no_classifiers_count: u32,
+    pub no_classifiers: Vec<MyFilteredProject>,
+    /// The unknown classifiers and the number of projects using them
+    pub unknown: HashMap<String, u32>,
+    pub unknown_classifiers_count: u32,
+    pub unknown_classifiers: Vec<MyFilteredProject>,
+    /// The deprecated classifiers and the number of projects using them
+    pub deprecated: HashMap<String, u32>,
+    pub deprecated_classifiers_count: u32,
+    pub deprecated_classifiers: Vec<MyFilteredProject>,
+    pub typed_count: u32,
+}
+
+pub fn is_deprecated(classifier: &str) -> bool {
+    DEPRECATED_CLASSIFIERS
+        .iter()
+        .any(|(deprecated, _)| *deprecated == classifier)
+}
+
+pub fn replacement(classifier: &str) -> Option<&'static str> {
+    DEPRECATED_CLASSIFIERS
+        .iter()
+        .find(|(deprecated, _)| *deprecated == classifier)
+        .and_then(|(_, replacement)| *replacement)
+}
+
+/// Neither a known nor a deprecated classifier.
+/// `Private :: ` classifiers are meant to be unknown so they are accepted.
+pub fn is_unknown(classifier: &str) -> bool {
+    !classifier.starts_with("Private :: ")
+        && !is_deprecated(classifier)
+        && !KNOWN_CLASSIFIERS.contains(classifier)
+}
+
+/// Split a classifier into its top level category and the rest,
+/// e.g. `Framework :: Django :: 5.2` into `Framework` and `Django :: 5.2`
+pub fn split_category(classifier: &str) -> (&str, &str) {
+    classifier.split_once(SEPARATOR).unwrap_or((classifier, ""))
+}
+
+/// The value of the `Development Status` classifier, e.g. `5 - Production/Stable`
+pub fn development_status(classifiers: &[String]) -> Option<String> {
+    classifiers
+        .iter()
+        .find_map(|classifier| match split_category(classifier) {
+            ("Development Status", status) if !status.is_empty() => Some(status.to_string()),
+            _ => None,
+        })
+}
+
+pub fn is_typed(classifiers: &[String]) -> bool {
+    classifiers
+        .iter()
+        .any(|classifier| classifier == "Typing :: Typed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_unknown() {
+        assert!(!is_unknown("Development Status :: 5 - Production/Stable"));
+        assert!(!is_unknown("Programming Language :: Python :: 3.12"));
+        assert!(!is_unknown("Typing :: Typed"));
+        assert!(!is_unknown("Private :: Do Not Upload"));
+        assert!(!is_unknown("Natural Language :: Ukranian"));
+        assert!(is_unknown("Development Status :: Stable"));
+        assert!(is_unknown("Programming Language :: Python :: 3.12 "));
+        assert!(is_unknown(""));
+    }
+
+    #[test]
+    fn test_deprecated() {
+        assert!(is_deprecated("Framework :: Opps"));
+        assert!(!is_deprecated("Framework :: Flask"));
+        assert_eq!(
+            replacement("Natural Language :: Ukranian"),
+            Some("Natural Language :: Ukrainian")
+        );
+        assert_eq!(replacement("Framework :: Opps"), None);
+    }
+
+    #[test]
+    fn test_known_classifiers_are_not_deprecated() {
+        for classifier in KNOWN_CLASSIFIERS.iter() {
+            assert!(!is_deprecated(classifier), "{classifier}");
+        }
+    }
+
+    #[test]
+    fn test_split_category() {
+        assert_eq!(
+            split_category("Framework :: Django :: 5.2"),
+            ("Framework", "Django :: 5.2")
+        );
+        assert_eq!(split_category("Typing"), ("Typing", ""));
+    }
+
+    #[test]
+    fn test_development_status_and_typed() {
+        let classifiers = vec![
+            String::from("Framework :: Flask"),
+            String::from("Development Status :: 4 - Beta"),
+            String::from("Typing :: Typed"),
+        ];
+        assert_eq!(
+            development_status(&classifiers),
+            Some(String::from("4 - Beta"))
+        );
+        assert!(is_typed(&classifiers));
+        assert_eq!(development_status(&[]), None);
+        assert!(!is_typed(&[]));
+    }
+}
@@ -0,0 +1,811 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::serde::ts_seconds;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use pydigger::repository::CanonicalRepository;
+use pydigger::stable_hash;
+
+/// The default limit of the size of the clone cache
+pub const DEFAULT_CACHE_SIZE_MB: u64 = 2048;
+/// The default limits of a single clone or fetch
+pub const DEFAULT_CLONE_TIMEOUT_SECS: u64 = 120;
+pub const DEFAULT_MAX_REPOSITORY_SIZE_MB: u64 = 500;
+
+/// How often we check if git is still within the limits
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const SIZE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+pub fn get_clones_path() -> String {
+    String::from("data/clones")
+}
+
+/// The clones of the repositories we keep between the runs.
+/// Each repository is in a folder named after the hash of its canonical URL,
+/// the index records when we last used it and its size.
+/// When the cache grows over its limit the least recently used clones are removed.
+#[derive(Debug)]
+pub struct CloneCache {
+    root: PathBuf,
+    max_size: u64,
+    /// Don't clone or fetch, only use the repositories already in the cache
+    no_clone: bool,
+    limits: CloneLimits,
+    /// Keep the whole history instead of the latest commit, see CloneCache::with_history
+    history: bool,
+}
+
+/// The limits of a single clone or fetch, git is killed when it crosses one of them
+#[derive(Debug, Clone)]
+pub struct CloneLimits {
+    pub timeout: Duration,
+    /// In bytes, including the .git folder
+    pub max_repository_size: u64,
+}
+
+/// The tagged commit of a release and what happened on the branch since
+#[derive(Debug, PartialEq, Eq)]
+pub struct TagHistory {
+    pub commit_date: DateTime<Utc>,
+    /// None if the tag is not on the branch we cloned, e.g. it is on a maintenance branch
+    pub commits_since: Option<u32>,
+}
+
+/// Why we could not get a repository.
+/// The outcome is recorded in MyProject::clone_outcome, see CloneError::outcome.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CloneError {
+    Timeout,
+    TooLarge,
+    AuthRequired,
+    NotFound,
+    Failed(String),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CacheEntry {
+    /// Relative to the root of the cache
+    path: String,
+    #[serde(with = "ts_seconds")]
+    last_used: DateTime<Utc>,
+    /// In bytes
+    size: u64,
+}
+
+/// The entries of the cache by canonical repository URL
+type CacheIndex = HashMap<String, CacheEntry>;
+
+impl Default for CloneLimits {
+    fn default() -> Self {
+        CloneLimits::new(DEFAULT_CLONE_TIMEOUT_SECS, DEFAULT_MAX_REPOSITORY_SIZE_MB)
+    }
+}
+
+impl CloneLimits {
+    pub fn new(timeout_secs: u64, max_repository_size_mb: u64) -> CloneLimits {
+        CloneLimits {
+            timeout: Duration::from_secs(timeout_secs),
+            max_repository_size: max_repository_size_mb * 1024 * 1024,
+        }
+    }
+}
+
+impl CloneError {
+    /// The value stored in MyProject::clone_outcome
+    pub fn outcome(&self) -> &'static str {
+        match self {
+            CloneError::Timeout => "timeout",
+            CloneError::TooLarge => "too-large",
+            CloneError::AuthRequired => "auth-required",
+            CloneError::NotFound => "not-found",
+            CloneError::Failed(_) => "failed",
+        }
+    }
+
+    /// Tell the reason from the error message of git
+    fn from_stderr(stderr: &str) -> CloneError {
+        let message = stderr.to_lowercase();
+        if message.contains("terminal prompts disabled")
+            || message.contains("authentication failed")
+            || message.contains("could not read username")
+            || message.contains("permission denied")
+        {
+            CloneError::AuthRequired
+        } else if message.contains("not found")
+            || message.contains("does not exist")
+            || message.contains("does not appear to be a git repository")
+        {
+            CloneError::NotFound
+        } else {
+            CloneError::Failed(stderr.trim().to_string())
+        }
+    }
+}
+
+impl fmt::Display for CloneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CloneError::Timeout => write!(f, "git did not finish in time"),
+            CloneError::TooLarge => write!(f, "the repository is too large"),
+            CloneError::AuthRequired => write!(f, "the repository requires authentication"),
+            CloneError::NotFound => write!(f, "the repository was not found"),
+            CloneError::Failed(message) => write!(f, "git failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for CloneError {}
+
+impl Default for CloneCache {
+    fn default() -> Self {
+        CloneCache::new(
+            Path::new(&get_clones_path()),
+            DEFAULT_CACHE_SIZE_MB,
+            false,
+            CloneLimits::default(),
+        )
+    }
+}
+
+impl CloneCache {
+    pub fn new(root: &Path, max_size_mb: u64, no_clone: bool, limits: CloneLimits) -> CloneCache {
+        CloneCache {
+            root: root.to_path_buf(),
+            max_size: max_size_mb * 1024 * 1024,
+            no_clone,
+            limits,
+            history: false,
+        }
+    }
+
+    /// Clone and fetch the whole history, it is needed to measure the activity of the repository
+    pub fn with_history(mut self, history: bool) -> CloneCache {
+        self.history = history;
+        self
+    }
+
+    pub fn has_history(&self) -> bool {
+        self.history
+    }
+
+    pub fn is_no_clone(&self) -> bool {
+        self.no_clone
+    }
+
+    /// Nothing from the metadata of the project ends up in the path, only the hash of the URL
+    fn relative_path(canonical: &CanonicalRepository) -> String {
+        format!("{:016x}", stable_hash(&canonical.url))
+    }
+
+    /// Refuse to clone into, fetch into or remove anything outside of the cache
+    fn ensure_in_cache(&self, path: &Path) -> Result<(), CloneError> {
+        match path.strip_prefix(&self.root) {
+            Ok(relative)
+                if relative.components().next().is_some()
+                    && relative
+                        .components()
+                        .all(|component| matches!(component, Component::Normal(_))) =>
+            {
+                Ok(())
+            }
+            _ => Err(CloneError::Failed(format!(
+                "{} is outside of the clone cache {}",
+                path.display(),
+                self.root.display()
+            ))),
+        }
+    }
+
+    fn remove(&self, path: &Path) {
+        match self.ensure_in_cache(path) {
+            Ok(()) => {
+                let _ = fs::remove_dir_all(path);
+            }
+            Err(err) => warn!("Not removing {:?}: {}", path, err),
+        }
+    }
+
+    /// The working tree of the repository: cloned the first time, fetched on the later runs.
+    /// In no-clone mode we return the cached clone of a remote repository as it is,
+    /// or None if it is not in the cache.
+    pub fn checkout(&self, canonical: &CanonicalRepository) -> Result<Option<PathBuf>, CloneError> {
+        let relative_path = CloneCache::relative_path(canonical);
+        let path = self.root.join(&relative_path);
+        let mut index = self.load_index();
+        // Clones from before the folders were named after the hash of the URL
+        if let Some(entry) = index.get(&canonical.url)
+            && entry.path != relative_path
+        {
+            self.remove(&self.root.join(&entry.path));
+            index.remove(&canonical.url);
+        }
+
+        // Local repositories are cloned even in no-clone mode, that does not need the network
+        if self.no_clone && canonical.platform != "local" {
+            if !path.join(".git").exists() {
+                return Ok(None);
+            }
+        } else if path.join(".git").exists() {
+            info!("Fetching {} into {:?}", canonical.url, path);
+            // The failure is often temporary, the clone from the earlier run is kept for the next one
+            self.fetch(&path)?;
+        } else {
+            info!("Cloning {} into {:?}", canonical.url, path);
+            if let Err(err) = self.clone(&canonical.url, &path) {
+                // Don't leave a half cloned or oversized repository behind
+                self.remove(&path);
+                index.remove(&canonical.url);
+                let _ = self.save_index(&index);
+                return Err(err);
+            }
+        }
+
+        index.insert(
+            canonical.url.clone(),
+            CacheEntry {
+                path: relative_path,
+                last_used: Utc::now(),
+                size: dir_size(&path),
+            },
+        );
+        self.evict(&mut index, &canonical.url);
+        if let Err(err) = self.save_index(&index) {
+            warn!("Could not save the index of the clone cache: {}", err);
+        }
+
+        Ok(Some(path))
+    }
+
+    /// The names of the tags in the origin of the clone.
+    /// The shallow clone does not have them, we ask the origin without downloading them.
+    pub fn tags(&self, path: &Path) -> Result<Vec<String>, CloneError> {
+        let output = self.git(
+            Some(path),
+            &["ls-remote", "--quiet", "--tags", "--refs", "origin"],
+            path,
+        )?;
+        Ok(output
+            .lines()
+            .filter_map(|line| line.split_once("refs/tags/"))
+            .map(|(_, tag)| tag.to_string())
+            .collect())
+    }
+
+    /// Fetch the tagged commit and the history of the branch since then.
+    /// Only the commits after the tag are fetched, and the tagged commit itself.
+    pub fn tag_history(&self, path: &Path, tag: &str) -> Result<TagHistory, CloneError> {
+        self.ensure_in_cache(path)?;
+        let git = |args: &[&str]| self.git(Some(path), args, path);
+        let refspec = format!("refs/tags/{tag}:refs/tags/{tag}");
+        git(&[
+            "fetch",
+            "--quiet",
+            "--no-tags",
+            "--depth",
+            "1",
+            "origin",
+            &refspec,
+        ])?;
+
+        let commit = format!("{tag}^{{commit}}");
+        let timestamp = git(&["log", "-1", "--format=%ct", &commit])?;
+        let commit_date = timestamp
+            .trim()
+            .parse()
+            .ok()
+            .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+            .ok_or_else(|| CloneError::Failed(format!("no date of the commit of tag {tag}")))?;
+
+        let is_ancestor = || git(&["merge-base", "--is-ancestor", &commit, "HEAD"]).is_ok();
+        // Deepen the branch down to the tag, and one more commit to connect the two
+        let on_branch = is_ancestor()
+            || (git(&[
+                "fetch",
+                "--quiet",
+                "--no-tags",
+                &format!("--shallow-exclude={tag}"),
+                "origin",
+            ])
+            .is_ok()
+                && git(&["fetch", "--quiet", "--no-tags", "--deepen=1", "origin"]).is_ok()
+                && is_ancestor());
+        let commits_since = if on_branch {
+            git(&["rev-list", "--count", "HEAD", &format!("^{commit}")])?
+                .trim()
+                .parse()
+                .ok()
+        } else {
+            None
+        };
+
+        Ok(TagHistory {
+            commit_date,
+            commits_since,
+        })
+    }
+
+    /// The date and the author of every commit of the branch, see RepositoryActivity::from_log
+    pub fn log(&self, path: &Path) -> Result<String, CloneError> {
+        self.git(Some(path), &["log", "--format=%ct%x09%aE", "HEAD"], path)
+    }
+
+    fn index_file(&self) -> PathBuf {
+        self.root.join("index.json")
+    }
+
+    fn load_index(&self) -> CacheIndex {
+        fs::read_to_string(self.index_file())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_index(&self, index: &CacheIndex) -> Result<(), Box<dyn std::error::Error>> {
+        fs::create_dir_all(&self.root)?;
+        fs::write(self.index_file(), serde_json::to_string_pretty(index)?)?;
+        Ok(())
+    }
+
+    /// Remove the least recently used clones until the cache fits its limit.
+    /// The clone we are about to use is never removed.
+    fn evict(&self, index: &mut CacheIndex, keep: &str) {
+        let mut total: u64 = index.values().map(|entry| entry.size).sum();
+        while total > self.max_size {
+            let Some(url) = index
+                .iter()
+                .filter(|(url, _)| *url != keep)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(url, _)| url.clone())
+            else {
+                break;
+            };
+            let entry = index.remove(&url).unwrap();
+            debug!("Removing {} from the clone cache", url);
+            self.remove(&self.root.join(&entry.path));
+            total -= entry.size;
+        }
+    }
+}
+
+impl CloneCache {
+    fn clone(&self, url: &str, path: &Path) -> Result<(), CloneError> {
+        self.ensure_in_cache(path)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| CloneError::Failed(err.to_string()))?;
+        }
+        let path = path.to_string_lossy();
+        let mut args = vec!["clone", "--quiet", "--no-recurse-submodules"];
+        if !self.history {
+            args.extend(["--depth", "1"]);
+        }
+        args.extend([url, &path]);
+        self.git(None, &args, Path::new(path.as_ref()))?;
+        Ok(())
+    }
+
+    /// Get the latest commit of the branch we cloned, or all the new commits if we keep the history
+    fn fetch(&self, path: &Path) -> Result<(), CloneError> {
+        self.ensure_in_cache(path)?;
+        let args: &[&str] = if !self.history {
+            &["fetch", "--quiet", "--depth", "1", "origin"]
+        } else if path.join(".git/shallow").exists() {
+            &["fetch", "--quiet", "--unshallow", "origin"]
+        } else {
+            &["fetch", "--quiet", "origin"]
+        };
+        self.git(Some(path), args, path)?;
+        self.git(
+            Some(path),
+            &["reset", "--quiet", "--hard", "FETCH_HEAD"],
+            path,
+        )?;
+        Ok(())
+    }
+
+    /// Run git and kill it if it runs too long or the repository in the watched folder grows too large.
+    /// Returns the output of git.
+    fn git(
+        &self,
+        path: Option<&Path>,
+        args: &[&str],
+        watched: &Path,
+    ) -> Result<String, CloneError> {
+        let mut child = git_command(path)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // git runs helpers such as git-remote-https and index-pack, they stay in its group
+            .process_group(0)
+            .spawn()
+            .map_err(|err| CloneError::Failed(err.to_string()))?;
+        // Read the output while waiting so git never blocks on a full pipe
+        let stdout = read_in_background(child.stdout.take().unwrap());
+        let stderr = read_in_background(child.stderr.take().unwrap());
+
+        let start = Instant::now();
+        let mut size_checked = start;
+        let status = loop {
+            if let Some(status) = child
+                .try_wait()
+                .map_err(|err| CloneError::Failed(err.to_string()))?
+            {
+                break status;
+            }
+            let error = if start.elapsed() > self.limits.timeout {
+                Some(CloneError::Timeout)
+            } else if size_checked.elapsed() > SIZE_CHECK_INTERVAL {
+                size_checked = Instant::now();
+                (dir_size(watched) > self.limits.max_repository_size)
+                    .then_some(CloneError::TooLarge)
+            } else {
+                None
+            };
+            if let Some(error) = error {
+                warn!("Stopping git {}: {}", args.join(" "), error);
+                kill_group(&child);
+                let _ = child.wait();
+                return Err(error);
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+
+        let stderr = stderr.join().unwrap_or_default();
+        if !status.success() {
+            return Err(CloneError::from_stderr(&stderr));
+        }
+        if dir_size(watched) > self.limits.max_repository_size {
+            return Err(CloneError::TooLarge);
+        }
+        Ok(stdout.join().unwrap_or_default())
+    }
+}
+
+/// Kill git and the processes it started, the group has the id of git
+fn kill_group(child: &Child) {
+    // SAFETY: kill only sends a signal, a negative id addresses the process group
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+fn read_in_background(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut text = String::new();
+        let _ = pipe.read_to_string(&mut text);
+        text
+    })
+}
+
+/// git that never asks for a password, runs hooks or downloads LFS objects
+fn git_command(path: Option<&Path>) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_LFS_SKIP_SMUDGE", "1")
+        .env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes")
+        .args([
+            "-c",
+            "core.hooksPath=/dev/null",
+            "-c",
+            "core.fsmonitor=false",
+            "-c",
+            "filter.lfs.smudge=",
+            "-c",
+            "filter.lfs.process=",
+            "-c",
+            "filter.lfs.required=false",
+        ]);
+    if let Some(path) = path {
+        cmd.arg("-C").arg(path);
+    }
+    cmd
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(_) => entry.metadata().map_or(0, |metadata| metadata.len()),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn cache(root: &Path, max_size_mb: u64, no_clone: bool) -> CloneCache {
+        CloneCache::new(root, max_size_mb, no_clone, CloneLimits::default())
+    }
+
+    /// A repository with a single commit to clone from
+    fn fixture_repository(path: &Path, content: &str) -> CanonicalRepository {
+        fixtures::create_repository(path, &[("README.md", content)]);
+        CanonicalRepository {
+            platform: String::from("gitea"),
+            host: String::from("example.org"),
+            owner: String::from("owner"),
+            repo: path.file_name().unwrap().to_string_lossy().to_string(),
+            url: format!("file://{}", path.display()),
+            branch: None,
+            subdirectory: None,
+        }
+    }
+
+    #[test]
+    fn test_checkout() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let origin = fixture_repository(&temp_folder.path().join("origin/tool"), "# tool\n");
+        let root = temp_folder.path().join("clones");
+
+        let offline = cache(&root, DEFAULT_CACHE_SIZE_MB, true);
+        assert_eq!(offline.checkout(&origin).unwrap(), None);
+
+        let cache = cache(&root, DEFAULT_CACHE_SIZE_MB, false);
+        let path = cache.checkout(&origin).unwrap().unwrap();
+        assert_eq!(path, root.join(CloneCache::relative_path(&origin)));
+        assert!(path.join("README.md").exists());
+
+        // The next run fetches the new commits
+        let origin_path = temp_folder.path().join("origin/tool");
+        fixtures::commit(&origin_path, &[("setup.py", "")], "second");
+        assert_eq!(cache.checkout(&origin).unwrap(), Some(path.clone()));
+        assert!(path.join("setup.py").exists());
+
+        assert_eq!(offline.checkout(&origin).unwrap(), Some(path.clone()));
+        assert!(cache.load_index().contains_key(&origin.url));
+
+        fixtures::git(&origin_path, &["tag", "v1.0"]);
+        fixtures::git(&origin_path, &["tag", "-a", "v1.1", "-m", "annotated"]);
+        assert_eq!(cache.tags(&path).unwrap(), vec!["v1.0", "v1.1"]);
+        assert_eq!(
+            cache.tag_history(&path, "v1.1").unwrap().commits_since,
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_tag_history() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let origin = fixture_repository(&temp_folder.path().join("origin/tool"), "# tool\n");
+        let origin_path = temp_folder.path().join("origin/tool");
+        fixtures::git(&origin_path, &["tag", "-a", "v1.0", "-m", "first release"]);
+        fixtures::git(&origin_path, &["checkout", "--quiet", "-b", "maintenance"]);
+        fixtures::commit(&origin_path, &[("fix.py", "")], "fix");
+        fixtures::git(&origin_path, &["tag", "v1.0.1"]);
+        fixtures::git(&origin_path, &["checkout", "--quiet", "-"]);
+        for message in ["second", "third"] {
+            fixtures::commit(&origin_path, &[(message, "")], message);
+        }
+
+        let cache = cache(
+            &temp_folder.path().join("clones"),
+            DEFAULT_CACHE_SIZE_MB,
+            false,
+        );
+        let path = cache.checkout(&origin).unwrap().unwrap();
+        let history = cache.tag_history(&path, "v1.0").unwrap();
+        assert_eq!(history.commits_since, Some(2));
+        assert!(history.commit_date <= Utc::now());
+
+        assert_eq!(
+            cache.tag_history(&path, "v1.0.1").unwrap().commits_since,
+            None
+        );
+        assert!(cache.tag_history(&path, "v9").is_err());
+    }
+
+    #[test]
+    fn test_history() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let origin = fixture_repository(&temp_folder.path().join("origin/tool"), "# tool\n");
+        let origin_path = temp_folder.path().join("origin/tool");
+        fixtures::commit(&origin_path, &[("setup.py", "")], "second");
+        let root = temp_folder.path().join("clones");
+
+        let path = cache(&root, DEFAULT_CACHE_SIZE_MB, false)
+            .checkout(&origin)
+            .unwrap()
+            .unwrap();
+        let shallow = cache(&root, DEFAULT_CACHE_SIZE_MB, false);
+        assert_eq!(shallow.log(&path).unwrap().lines().count(), 1);
+
+        // The shallow clone of the earlier run gets the whole history
+        fixtures::commit(&origin_path, &[("setup.cfg", "")], "third");
+        let history = cache(&root, DEFAULT_CACHE_SIZE_MB, false).with_history(true);
+        history.checkout(&origin).unwrap();
+        assert_eq!(history.log(&path).unwrap().lines().count(), 3);
+    }
+
+    #[test]
+    fn test_evict_least_recently_used() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let first = fixture_repository(&temp_folder.path().join("origin/first"), "# first\n");
+        let second = fixture_repository(&temp_folder.path().join("origin/second"), "# second\n");
+        let root = temp_folder.path().join("clones");
+
+        // Too small to hold both clones
+        let cache = cache(&root, 0, false);
+        let first_path = cache.checkout(&first).unwrap().unwrap();
+        assert!(first_path.exists());
+        let second_path = cache.checkout(&second).unwrap().unwrap();
+        assert!(second_path.exists());
+        assert!(!first_path.exists());
+
+        let index = cache.load_index();
+        assert_eq!(index.len(), 1);
+        assert!(index.contains_key(&second.url));
+    }
+
+    #[test]
+    fn test_clone_errors() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let mut origin = fixture_repository(&temp_folder.path().join("origin/tool"), "# tool\n");
+        let root = temp_folder.path().join("clones");
+
+        let small = CloneCache::new(
+            &root,
+            DEFAULT_CACHE_SIZE_MB,
+            false,
+            CloneLimits {
+                timeout: Duration::from_secs(DEFAULT_CLONE_TIMEOUT_SECS),
+                max_repository_size: 0,
+            },
+        );
+        assert_eq!(small.checkout(&origin), Err(CloneError::TooLarge));
+        assert!(!root.join(CloneCache::relative_path(&origin)).exists());
+
+        origin.url = format!("file://{}", temp_folder.path().join("missing").display());
+        assert_eq!(
+            cache(&root, DEFAULT_CACHE_SIZE_MB, false).checkout(&origin),
+            Err(CloneError::NotFound)
+        );
+    }
+
+    #[test]
+    fn test_timeout_stops_the_processes_of_git() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let watched = temp_folder.path().join("clone");
+        fs::create_dir_all(&watched).unwrap();
+        let pid_file = temp_folder.path().join("pid");
+        let cache = CloneCache::new(
+            &temp_folder.path().join("clones"),
+            DEFAULT_CACHE_SIZE_MB,
+            false,
+            CloneLimits {
+                timeout: Duration::from_secs(1),
+                max_repository_size: DEFAULT_MAX_REPOSITORY_SIZE_MB * 1024 * 1024,
+            },
+        );
+        // A helper started by git that keeps writing into the clone, like index-pack
+        let alias = format!(
+            "alias.grow=!echo $$ > {}; while true; do echo data >> {}; sleep 0.1; done",
+            pid_file.display(),
+            watched.join("pack").display()
+        );
+        assert_eq!(
+            cache.git(None, &["-c", &alias, "grow"], &watched),
+            Err(CloneError::Timeout)
+        );
+
+        let pid = fs::read_to_string(&pid_file).unwrap();
+        thread::sleep(Duration::from_millis(300));
+        let size = dir_size(&watched);
+        thread::sleep(Duration::from_millis(500));
+        assert_eq!(dir_size(&watched), size);
+        // Gone, or a zombie waiting for its new parent
+        let stat = fs::read_to_string(format!("/proc/{}/stat", pid.trim())).unwrap_or_default();
+        assert!(stat.is_empty() || stat.contains(") Z "), "{stat}");
+    }
+
+    #[test]
+    fn test_fetch_error_keeps_the_clone() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let origin_path = temp_folder.path().join("origin/tool");
+        let origin = fixture_repository(&origin_path, "# tool\n");
+        let cache = cache(
+            &temp_folder.path().join("clones"),
+            DEFAULT_CACHE_SIZE_MB,
+            false,
+        );
+        let path = cache.checkout(&origin).unwrap().unwrap();
+
+        // The origin is gone for a while
+        let moved = temp_folder.path().join("origin/moved");
+        fs::rename(&origin_path, &moved).unwrap();
+        assert_eq!(cache.checkout(&origin), Err(CloneError::NotFound));
+        assert!(path.join("README.md").exists());
+        assert!(cache.load_index().contains_key(&origin.url));
+
+        fs::rename(&moved, &origin_path).unwrap();
+        assert_eq!(cache.checkout(&origin).unwrap(), Some(path));
+    }
+
+    #[test]
+    fn test_cache_paths() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let root = temp_folder.path().join("clones");
+        let cache = cache(&root, DEFAULT_CACHE_SIZE_MB, false);
+        let mut canonical = fixture_repository(&temp_folder.path().join("origin/tool"), "");
+        canonical.owner = String::from("../../..");
+        canonical.repo = String::from("..");
+        let relative_path = CloneCache::relative_path(&canonical);
+        assert_eq!(relative_path.len(), 16);
+        assert!(cache.ensure_in_cache(&root.join(&relative_path)).is_ok());
+
+        for outside in [
+            root.clone(),
+            root.join(".."),
+            root.join("a/../../b"),
+            temp_folder.path().join("origin/tool"),
+        ] {
+            assert!(cache.ensure_in_cache(&outside).is_err(), "{outside:?}");
+        }
+        cache.remove(&temp_folder.path().join("origin/tool"));
+        assert!(temp_folder.path().join("origin/tool/README.md").exists());
+    }
+
+    #[test]
+    fn test_checkout_replaces_the_old_layout() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let origin = fixture_repository(&temp_folder.path().join("origin/tool"), "# tool\n");
+        let root = temp_folder.path().join("clones");
+        let cache = cache(&root, DEFAULT_CACHE_SIZE_MB, false);
+        let old_path = root.join("example.org/owner/tool");
+        fs::create_dir_all(&old_path).unwrap();
+        let mut index = CacheIndex::new();
+        index.insert(
+            origin.url.clone(),
+            CacheEntry {
+                path: String::from("example.org/owner/tool"),
+                last_used: Utc::now(),
+                size: 0,
+            },
+        );
+        cache.save_index(&index).unwrap();
+
+        let path = cache.checkout(&origin).unwrap().unwrap();
+        assert!(!old_path.exists());
+        assert_eq!(
+            cache.load_index()[&origin.url].path,
+            CloneCache::relative_path(&origin)
+        );
+        assert!(path.join("README.md").exists());
+    }
+
+    #[test]
+    fn test_from_stderr() {
+        assert_eq!(
+            CloneError::from_stderr(
+                "fatal: could not read Username for 'https://github.com': terminal prompts disabled"
+            ),
+            CloneError::AuthRequired
+        );
+        assert_eq!(
+            CloneError::from_stderr(
+                "remote: Repository not found.\nfatal: repository 'https://github.com/a/b/' not found"
+            ),
+            CloneError::NotFound
+        );
+        assert_eq!(
+            CloneError::from_stderr("fatal: unable to access: Could not resolve host").outcome(),
+            "failed"
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::MyFilteredProject;
+use crate::risk::TINY_DESCRIPTION;
+
+/// Descriptions longer than this many characters usually carry the whole changelog or documentation
+pub const HUGE_DESCRIPTION: usize = 100_000;
+
+/// Text left in the long description by templates and tools.
+/// The phrases also match at the beginning of the description.
+const PLACEHOLDERS: [&str; 7] = [
+    "unknown",
+    "none",
+    "add your description here",
+    "a short description of the project",
+    "lorem ipsum",
+    "# project title",
+    "todo: write",
+];
+
+static MARKDOWN_IMAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"!\[[^\]]*\]\(\s*<?([^)\s>]+)"#).unwrap());
+static HTML_IMAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)<img\s[^>]*src\s*=\s*["']([^"']+)["']"#).unwrap());
+static RST_IMAGE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*\.\. (?:\|[^|]+\| )?(?:image|figure)::\s*(\S+)").unwrap()
+});
+
+static MARKDOWN_MARKERS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        r"(?m)^#{1,6} \S",
+        r"(?m)^```",
+        r"\[[^\]]+\]\([^)]+\)",
+        r"(?m)^\s*[-*] \[[ x]\] ",
+        r"\*\*[^*\n]+\*\*",
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).unwrap())
+    .collect()
+});
+static RST_MARKERS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        r"(?m)^(?:={3,}|-{3,}|~{3,}|\^{3,}|\*{3,})\s*$",
+        r"(?m)^\.\. [a-z-]+::",
+        r"`[^`\n]+ <[^>\n]+>`_",
+        r"(?m)^\.\. _[^:]+:",
+        r":[a-z]+:`[^`]+`",
+        r"`[^`\n]+`_\b",
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).unwrap())
+    .collect()
+});
+
+/// What we found in the long description of the project
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct DescriptionAnalysis {
+    /// The format declared in description_content_type: markdown, rst, plain or other.
+    /// PyPI renders the description as reStructuredText if it is not declared.
+    pub declared_format: String,
+    /// The format the text looks like: markdown, rst, plain or empty
+    pub detected_format: String,
+    pub placeholder: bool,
+    /// Images with relative paths, they don't render on PyPI
+    pub relative_images: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DescriptionReport {
+    /// Number of projects with each declared format
+    pub declared_formats: HashMap<String, u32>,
+    /// Number of projects with each detected format
+    pub detected_formats: HashMap<String, u32>,
+    pub empty_count: u32,
+    pub empty: Vec<MyFilteredProject>,
+    pub placeholder_count: u32,
+    pub placeholder: Vec<MyFilteredProject>,
+    pub format_mismatch_count: u32,
+    pub format_mismatch: Vec<MyFilteredProject>,
+    pub relative_images_count: u32,
+    pub relative_images: Vec<MyFilteredProject>,
+    /// Number of projects in each size bucket, see size_bucket
+    pub sizes: HashMap<String, u32>,
+    pub huge_count: u32,
+    /// The projects with descriptions over HUGE_DESCRIPTION characters, the longest first
+    pub huge: Vec<MyFilteredProject>,
+}
+
+impl DescriptionAnalysis {
+    pub fn analyze(description: &str, content_type: Option<&str>) -> DescriptionAnalysis {
+        DescriptionAnalysis {
+            declared_format: declared_format(content_type),
+            detected_format: detect_format(description),
+            placeholder: is_placeholder(description),
+            relative_images: relative_images(description),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.detected_format == "empty"
+    }
+
+    /// The text is written in a markup PyPI will not render because a different one was declared,
+    /// e.g. Markdown without `text/markdown` or with `text/plain` shows up as a wall of raw text.
+    pub fn is_format_mismatch(&self) -> bool {
+        matches!(self.detected_format.as_str(), "markdown" | "rst")
+            && matches!(self.declared_format.as_str(), "markdown" | "rst" | "plain")
+            && self.detected_format != self.declared_format
+    }
+
+    /// The PyPI page of the project will look broken or empty
+    pub fn looks_broken(&self) -> bool {
+        self.is_empty()
+            || self.placeholder
+            || self.is_format_mismatch()
+            || !self.relative_images.is_empty()
+    }
+}
+
+/// The format from the content type, e.g. `text/markdown; charset=UTF-8; variant=GFM`
+pub fn declared_format(content_type: Option<&str>) -> String {
+    let Some(content_type) = content_type else {
+        return String::from("rst");
+    };
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_lowercase();
+    match mime.as_str() {
+        "" | "text/x-rst" => String::from("rst"),
+        "text/markdown" => String::from("markdown"),
+        "text/plain" => String::from("plain"),
+        _ => String::from("other"),
+    }
+}
+
+/// Guess the format by counting the Markdown and reStructuredText constructs
+pub fn detect_format(description: &str) -> String {
+    if description.trim().is_empty() {
+        return String::from("empty");
+    }
+    let count = |markers: &[Regex]| -> usize {
+        markers
+            .iter()
+            .map(|marker| marker.find_iter(description).count())
+            .sum()
+    };
+    let markdown = count(&MARKDOWN_MARKERS);
+    let rst = count(&RST_MARKERS);
+    if markdown == 0 && rst == 0 {
+        String::from("plain")
+    } else if markdown >= rst {
+        String::from("markdown")
+    } else {
+        String::from("rst")
+    }
+}
+
+/// The size of the description in characters: empty, tiny (under risk::TINY_DESCRIPTION),
+/// short (under 1000), medium (under 10000), long, or huge (over HUGE_DESCRIPTION)
+pub fn size_bucket(length: usize) -> &'static str {
+    match length {
+        0 => "empty",
+        length if length < TINY_DESCRIPTION => "tiny",
+        length if length < 1_000 => "short",
+        length if length < 10_000 => "medium",
+        length if length <= HUGE_DESCRIPTION => "long",
+        _ => "huge",
+    }
+}
+
+pub fn is_placeholder(description: &str) -> bool {
+    let text = description.trim().to_lowercase();
+    !text.is_empty()
+        && PLACEHOLDERS.iter().any(|placeholder| {
+            text == *placeholder || (placeholder.contains(' ') && text.starts_with(placeholder))
+        })
+}
+
+/// The images referenced with a relative path in Markdown, reStructuredText or HTML
+pub fn relative_images(description: &str) -> Vec<String> {
+    let mut images: Vec<String> = [&*MARKDOWN_IMAGE, &*HTML_IMAGE, &*RST_IMAGE]
+        .iter()
+        .flat_map(|pattern| pattern.captures_iter(description))
+        .map(|caps| caps[1].to_string())
+        .filter(|src| is_relative(src))
+        .collect();
+    images.sort();
+    images.dedup();
+    images
+}
+
+fn is_relative(src: &str) -> bool {
+    let src = src.to_lowercase();
+    !(src.starts_with("http://")
+        || src.starts_with("https://")
+        || src.starts_with("//")
+        || src.starts_with("data:"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_declared_format() {
+        assert_eq!(declared_format(None), "rst");
+        assert_eq!(declared_format(Some("")), "rst");
+        assert_eq!(
+            declared_format(Some("text/markdown; charset=UTF-8; variant=GFM")),
+            "markdown"
+        );
+        assert_eq!(declared_format(Some("text/x-rst")), "rst");
+        assert_eq!(declared_format(Some("text/plain")), "plain");
+        assert_eq!(declared_format(Some("text/html")), "other");
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(detect_format(" \n"), "empty");
+        assert_eq!(detect_format("Just some words."), "plain");
+        assert_eq!(
+            detect_format("# Title\n\nSee [the docs](https://example.org).\n"),
+            "markdown"
+        );
+        assert_eq!(
+            detect_format("Title\n=====\n\nSee `the docs <https://example.org>`_.\n"),
+            "rst"
+        );
+    }
+
+    #[test]
+    fn test_is_placeholder() {
+        assert!(is_placeholder("UNKNOWN"));
+        assert!(is_placeholder(
+            "# Project Title\n\nOne Paragraph of project description"
+        ));
+        assert!(!is_placeholder(""));
+        assert!(!is_placeholder("Unknown Horizons is a strategy game"));
+        assert!(!is_placeholder("# pixelcore\n"));
+    }
+
+    #[test]
+    fn test_relative_images() {
+        let description = r#"
+![logo](docs/logo.png)
+![badge](https://img.shields.io/pypi/v/flask.svg)
+<img src="./screenshot.png" alt="">
+<img src="https://example.org/a.png">
+
+.. image:: images/diagram.svg
+.. |badge| image:: https://example.org/badge.svg
+"#;
+        assert_eq!(
+            relative_images(description),
+            vec!["./screenshot.png", "docs/logo.png", "images/diagram.svg"]
+        );
+    }
+
+    #[test]
+    fn test_analyze() {
+        let analysis = DescriptionAnalysis::analyze("# Title\n\n**bold**\n", None);
+        assert_eq!(analysis.declared_format, "rst");
+        assert_eq!(analysis.detected_format, "markdown");
+        assert!(analysis.is_format_mismatch());
+        assert!(analysis.looks_broken());
+
+        let analysis = DescriptionAnalysis::analyze("# Title\n", Some("text/markdown"));
+        assert!(!analysis.is_format_mismatch());
+        assert!(!analysis.looks_broken());
+
+        assert!(DescriptionAnalysis::analyze("", Some("text/markdown")).looks_broken());
+
+        let analysis = DescriptionAnalysis::analyze(
+            "# Title\n\nSee [the docs](https://example.org).\n",
+            Some("text/plain"),
+        );
+        assert_eq!(analysis.declared_format, "plain");
+        assert!(analysis.is_format_mismatch());
+        let analysis =
+            DescriptionAnalysis::analyze("Title\n=====\n\nSome text.\n", Some("text/plain"));
+        assert!(analysis.is_format_mismatch());
+        assert!(
+            !DescriptionAnalysis::analyze("Just some words.", Some("text/plain"))
+                .is_format_mismatch()
+        );
+    }
+
+    #[test]
+    fn test_size_bucket() {
+        assert_eq!(size_bucket(0), "empty");
+        assert_eq!(size_bucket(42), "tiny");
+        assert_eq!(size_bucket(800), "short");
+        assert_eq!(size_bucket(5_000), "medium");
+        assert_eq!(size_bucket(HUGE_DESCRIPTION), "long");
+        assert_eq!(size_bucket(HUGE_DESCRIPTION + 1), "huge");
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::wheels::WheelTags;
+use crate::{MyFilteredProject, UrlInfo};
+
+/// A file uploaded for the release, from the urls array of the PyPI JSON
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct DistributionFile {
+    pub filename: String,
+    pub packagetype: Option<String>,
+    pub size: Option<u64>,
+    pub sha256: Option<String>,
+    #[serde(default, with = "ts_seconds_option")]
+    pub upload_time: Option<DateTime<Utc>>,
+    pub python_version: Option<String>,
+    pub requires_python: Option<String>,
+    pub yanked: bool,
+}
+
+/// What kind of files were uploaded for a release
+#[derive(Debug, PartialEq, Eq)]
+pub enum DistributionKind {
+    SdistOnly,
+    WheelOnly,
+    Both,
+    /// Neither sdist nor wheel, e.g. only eggs or no files at all
+    Other,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DistributionReport {
+    /// Number of releases by kind: sdist_only, wheel_only, both, other
+    pub kinds: HashMap<String, u32>,
+    pub pure_python_count: u32,
+    pub platform_wheels_count: u32,
+    /// Number of releases with wheels for each platform tag
+    pub platforms: HashMap<String, u32>,
+    /// Number of releases with wheels for each ABI tag
+    pub abis: HashMap<String, u32>,
+    pub no_sdist_count: u32,
+    pub no_sdist: Vec<MyFilteredProject>,
+    pub no_wheel_count: u32,
+    pub no_wheel: Vec<MyFilteredProject>,
+}
+
+impl DistributionFile {
+    pub fn from_url_info(url_info: &UrlInfo) -> DistributionFile {
+        DistributionFile {
+            filename: url_info.filename.clone().unwrap_or_default(),
+            packagetype: url_info.packagetype.clone(),
+            size: url_info.size,
+            sha256: url_info
+                .digests
+                .as_ref()
+                .and_then(|digests| digests.sha256.clone()),
+            upload_time: url_info.upload_time_iso_8601,
+            python_version: url_info.python_version.clone(),
+            requires_python: url_info.requires_python.clone(),
+            yanked: url_info.yanked.unwrap_or(false),
+        }
+    }
+
+    pub fn is_sdist(&self) -> bool {
+        self.packagetype.as_deref() == Some("sdist")
+    }
+
+    pub fn is_wheel(&self) -> bool {
+        self.packagetype.as_deref() == Some("bdist_wheel") || self.filename.ends_with(".whl")
+    }
+
+    pub fn wheel_tags(&self) -> Option<WheelTags> {
+        WheelTags::parse(&self.filename)
+    }
+
+    /// A wheel that works everywhere, tagged `none-any`
+    pub fn is_pure_python_wheel(&self) -> bool {
+        self.wheel_tags().is_some_and(|tags| tags.is_pure_python())
+    }
+}
+
+pub fn distribution_kind(files: &[DistributionFile]) -> DistributionKind {
+    let has_sdist = files.iter().any(|file| file.is_sdist());
+    let has_wheel = files.iter().any(|file| file.is_wheel());
+    match (has_sdist, has_wheel) {
+        (true, true) => DistributionKind::Both,
+        (true, false) => DistributionKind::SdistOnly,
+        (false, true) => DistributionKind::WheelOnly,
+        (false, false) => DistributionKind::Other,
+    }
+}
+
+/// The platform tags of all the wheels
+pub fn platforms(files: &[DistributionFile]) -> Vec<String> {
+    let mut platforms: Vec<String> = files
+        .iter()
+        .filter_map(|file| file.wheel_tags())
+        .flat_map(|tags| tags.platform)
+        .collect();
+    platforms.sort();
+    platforms.dedup();
+    platforms
+}
+
+pub fn abis(files: &[DistributionFile]) -> Vec<String> {
+    let mut abis: Vec<String> = files
+        .iter()
+        .filter_map(|file| file.wheel_tags())
+        .flat_map(|tags| tags.abi)
+        .collect();
+    abis.sort();
+    abis.dedup();
+    abis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(filename: &str, packagetype: &str) -> DistributionFile {
+        DistributionFile {
+            filename: String::from(filename),
+            packagetype: Some(String::from(packagetype)),
+            size: None,
+            sha256: None,
+            upload_time: None,
+            python_version: None,
+            requires_python: None,
+            yanked: false,
+        }
+    }
+
+    #[test]
+    fn test_is_pure_python_wheel() {
+        assert!(file("flask-3.1.2-py3-none-any.whl", "bdist_wheel").is_pure_python_wheel());
+        assert!(!file("pkg-1.0-cp312-cp312-win_amd64.whl", "bdist_wheel").is_pure_python_wheel());
+        assert!(!file("flask-3.1.2.tar.gz", "sdist").is_pure_python_wheel());
+    }
+
+    #[test]
+    fn test_distribution_kind() {
+        let sdist = file("pkg-1.0.tar.gz", "sdist");
+        let wheel = file("pkg-1.0-py3-none-any.whl", "bdist_wheel");
+        let egg = file("pkg-1.0-py2.7.egg", "bdist_egg");
+        assert_eq!(
+            distribution_kind(&[sdist.clone(), wheel.clone()]),
+            DistributionKind::Both
+        );
+        assert_eq!(distribution_kind(&[sdist]), DistributionKind::SdistOnly);
+        assert_eq!(distribution_kind(&[wheel]), DistributionKind::WheelOnly);
+        assert_eq!(distribution_kind(&[egg]), DistributionKind::Other);
+        assert_eq!(distribution_kind(&[]), DistributionKind::Other);
+    }
+
+    #[test]
+    fn test_platforms_and_abis() {
+        let files = vec![
+            file("pkg-1.0.tar.gz", "sdist"),
+            file("pkg-1.0-cp312-cp312-win_amd64.whl", "bdist_wheel"),
+            file("pkg-1.0-cp313-cp313-win_amd64.whl", "bdist_wheel"),
+            file("pkg-1.0-cp39-abi3-macosx_11_0_arm64.whl", "bdist_wheel"),
+            file(
+                "pkg-1.0-cp312-cp312-manylinux_2_17_x86_64.manylinux2014_x86_64.whl",
+                "bdist_wheel",
+            ),
+        ];
+        assert_eq!(
+            platforms(&files),
+            vec![
+                "macosx_11_0_arm64",
+                "manylinux2014_x86_64",
+                "manylinux_2_17_x86_64",
+                "win_amd64"
+            ]
+        );
+        assert_eq!(abis(&files), vec!["abi3", "cp312", "cp313"]);
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::fs;
+use std::path::Path;
 
 use chrono::serde::ts_seconds;
 use chrono::{DateTime, Utc};
@@ -7,13 +8,37 @@ use regex::Regex;
 use reqwest::blocking::get;
 use rss::Channel;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, error, info};
-
-use git_digger::Repository;
+use tracing::{debug, error, info, warn};
 
+use crate::clones::CloneCache;
 use crate::{Args, report};
 use pydigger::MyProject;
 use pydigger::PyPiProject;
+use pydigger::activity::RepositoryActivity;
+use pydigger::ci;
+use pydigger::classifiers;
+use pydigger::description::DescriptionAnalysis;
+use pydigger::distributions::DistributionFile;
+use pydigger::normalize_package_name;
+use pydigger::people;
+use pydigger::provenance::Provenance;
+use pydigger::python_versions;
+use pydigger::repository::{self, CanonicalRepository, VcsHosts};
+use pydigger::risk;
+use pydigger::tags;
+use pydigger::typosquatting::PopularNames;
+use pydigger::urls;
+use pydigger::wheels::PlatformCoverage;
+
+/// The settings of the analysis that don't change from project to project
+#[derive(Debug, Default)]
+pub struct CollectConfig {
+    pub popular_names: PopularNames,
+    pub vcs_hosts: VcsHosts,
+    pub clone_cache: CloneCache,
+    /// The local repositories given by the operator, by normalized project name, see local_repositories
+    pub local_repositories: HashMap<String, CanonicalRepository>,
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CollectStats {
@@ -39,7 +64,7 @@ pub fn download_json_for_project(
     let response = reqwest::blocking::get(&url)?;
     if response.status().is_success() {
         let json = response.text()?;
-        return Ok(json);
+        Ok(json)
     } else {
         Err(format!(
             "Failed to download JSON for project {} version {}: HTTP {}",
@@ -50,6 +75,48 @@ pub fn download_json_for_project(
     }
 }
 
+/// Splits NAME==VERSION into (name, version), the version is empty if it was not given
+pub fn split_name_version(project: &str) -> (String, String) {
+    match project.split_once("==") {
+        Some((name, version)) => (name.trim().to_string(), version.trim().to_string()),
+        None => (project.trim().to_string(), String::new()),
+    }
+}
+
+/// Parses the NAME=PATH pairs of --local-repository, the PATH is absolute or a file:// URL.
+/// The invalid pairs are logged and skipped.
+pub fn local_repositories(pairs: &[String]) -> HashMap<String, CanonicalRepository> {
+    let mut repositories = HashMap::new();
+    for pair in pairs {
+        let canonical = pair
+            .split_once('=')
+            .and_then(|(name, path)| Some((name, repository::canonicalize_local(path)?)));
+        match canonical {
+            Some((name, canonical)) if !name.trim().is_empty() => {
+                repositories.insert(normalize_package_name(name), canonical);
+            }
+            _ => error!(
+                "Invalid local repository '{}', expected NAME=PATH with an absolute PATH",
+                pair
+            ),
+        }
+    }
+    repositories
+}
+
+/// Reads the list of projects from a file, one NAME or NAME==VERSION per line.
+/// Empty lines and lines starting with # are skipped.
+pub fn read_projects_file(path: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(path)?;
+    let projects = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect();
+    Ok(projects)
+}
+
 /// Extracts (name, version) from PyPI project links of the format https://pypi.org/project/NAME/VERSION/
 pub fn extract_name_version(link: &str) -> Option<(String, String)> {
     let re = Regex::new(r"https://pypi\.org/project/([^/]+)/([^/]+)/?").ok()?;
@@ -124,7 +191,7 @@ pub fn save_download_stats(cs: CollectStats) -> Result<(), Box<dyn std::error::E
     Ok(())
 }
 
-pub fn download_project_json(args: &Args) -> CollectStats {
+pub fn download_project_json(args: &Args, config: &CollectConfig) -> CollectStats {
     let start_date = Utc::now();
     let mut projects_in_rss = 0;
     let mut downloaded_projects = 0;
@@ -137,7 +204,7 @@ pub fn download_project_json(args: &Args) -> CollectStats {
                 let limit = args.limit.unwrap_or(items.len());
                 projects_in_rss = items.len() as u32;
                 (downloaded_projects, skipped_projects, error_projects) =
-                    process_items(&items, limit);
+                    process_items(items, limit, config);
             }
             Err(e) => error!("Error parsing RSS feed: {}", e),
         },
@@ -155,13 +222,13 @@ pub fn download_project_json(args: &Args) -> CollectStats {
         elapsed_time,
     }
 }
-fn process_items(items: &[rss::Item], limit: usize) -> (u32, u32, u32) {
+fn process_items(items: &[rss::Item], limit: usize, config: &CollectConfig) -> (u32, u32, u32) {
     let mut downloaded_projects = 0;
     let mut skipped_projects = 0;
     let mut error_projects = 0;
 
     for item in items.iter().take(limit) {
-        match process_item(item) {
+        match process_item(item, config) {
             Ok(status) => match status {
                 Status::Success => downloaded_projects += 1,
                 Status::DateError => error_projects += 1,
@@ -185,7 +252,10 @@ enum Status {
     Skipping,
 }
 
-fn process_item(item: &rss::Item) -> Result<Status, Box<dyn std::error::Error>> {
+fn process_item(
+    item: &rss::Item,
+    config: &CollectConfig,
+) -> Result<Status, Box<dyn std::error::Error>> {
     info!("Item: {}", item.link().unwrap_or("No link"));
     debug!("Title: {}", item.title().unwrap_or("No title"));
 
@@ -212,26 +282,57 @@ fn process_item(item: &rss::Item) -> Result<Status, Box<dyn std::error::Error>>
     if let Some((name, version)) = extract_name_version(link) {
         //println!("Extracted Name: {}, Version: {}", name, version);
         // Only download the json if we don't have it already
-        if let Ok(saved_project) = load_mt_project_from_file(&name) {
-            if saved_project.pub_date >= pub_date {
-                info!("Project {} is up to date, skipping download.", name);
-                return Ok(Status::Skipping);
-            };
-        }
-        handle_project(name, version, pub_date)?;
+        if let Ok(saved_project) = load_mt_project_from_file(&name)
+            && saved_project.pub_date >= pub_date
+        {
+            info!("Project {} is up to date, skipping download.", name);
+            return Ok(Status::Skipping);
+        };
+        handle_project(name, version, Some(pub_date), config)?;
     }
     Ok(Status::Success)
 }
 
+/// Download and analyze a project.
+/// If the publication date is not known (e.g. not from the RSS feed) we use the upload time of the release.
 pub fn handle_project(
     name: String,
     version: String,
-    pub_date: DateTime<Utc>,
+    pub_date: Option<DateTime<Utc>>,
+    config: &CollectConfig,
 ) -> Result<(), Box<dyn std::error::Error + 'static>> {
     let project_json_from_pypi = download_json_for_project(&name, &version)?;
 
-    let mut my_project = analyze_project_json_from_pypi(&project_json_from_pypi, pub_date);
-    handle_vcs(&mut my_project);
+    let mut my_project = analyze_project_json_from_pypi(&project_json_from_pypi, pub_date)?;
+    my_project.name_suspicion = config.popular_names.check(&my_project.name);
+    if let Some(suspicion) = &my_project.name_suspicion {
+        warn!(
+            "Project {} looks like {} ({}), score {}",
+            my_project.name,
+            suspicion.similar_to,
+            suspicion.reasons.join(", "),
+            suspicion.score
+        );
+    }
+    if my_project.release_stats.is_none() && !version.is_empty() {
+        // The version specific JSON has no releases, we need the JSON of the whole project
+        match download_json_for_project(&name, "") {
+            Ok(json) => match serde_json::from_str::<PyPiProject>(&json) {
+                Ok(project) => my_project.process_releases(&project),
+                Err(e) => error!("Error parsing JSON of project {}: {}", name, e),
+            },
+            Err(e) => error!("Error downloading releases of project {}: {}", name, e),
+        }
+    }
+    if let Ok(saved_project) = load_mt_project_from_file(&my_project.name) {
+        my_project.previous_version = if saved_project.version == my_project.version {
+            saved_project.previous_version
+        } else {
+            Some(saved_project.version)
+        };
+    }
+    handle_vcs(&mut my_project, config);
+    my_project.risk_factors = risk::risk_factors(&my_project);
     save_my_project_to_file(&my_project).unwrap_or_else(|e| {
         error!("Error saving myproject JSON to file: {}", e);
     });
@@ -240,11 +341,21 @@ pub fn handle_project(
 
 fn analyze_project_json_from_pypi(
     project_json_from_pypi: &str,
-    pub_date: DateTime<Utc>,
-) -> MyProject {
-    let project = serde_json::from_str::<PyPiProject>(&project_json_from_pypi).unwrap();
+    pub_date: Option<DateTime<Utc>>,
+) -> Result<MyProject, Box<dyn std::error::Error>> {
+    let project = serde_json::from_str::<PyPiProject>(project_json_from_pypi)?;
     info!("Handle project download: {}", project.info.name);
 
+    let pub_date = pub_date
+        .or_else(|| project.upload_time())
+        .unwrap_or_else(|| {
+            warn!(
+                "No upload time found for project {}, using the current time",
+                project.info.name
+            );
+            Utc::now()
+        });
+
     // TODO: collect the various project URLs so we can learn what names do people use
     // I've seen:
     // Homepage, Issues, Repository, Source, Documentation, Github, API Documentation
@@ -261,27 +372,74 @@ fn analyze_project_json_from_pypi(
     let mut my_project = MyProject {
         name: project.info.name.clone(),
         version: project.info.version.clone(),
+        previous_version: None,
         summary: project.info.summary.clone(),
+        description_length: Some(project.info.description.chars().count()),
+        description: Some(DescriptionAnalysis::analyze(
+            &project.info.description,
+            project.info.description_content_type.as_deref(),
+        )),
         license: project.info.license.clone(),
         license_expression: project.info.license_expression.clone(),
         download: None,
         download_source: None,
         repository: None,
         repository_source: None,
+        repository_candidates: vec![],
+        canonical_repository: None,
         pub_date,
         home_page: None,
         home_page_source: None,
         maintainer: project.info.maintainer.clone(),
+        maintainer_email: project.info.maintainer_email.clone(),
         author: project.info.author.clone(),
-        project_urls: project_urls,
+        author_email: project.info.author_email.clone(),
+        authors: people::parse_people(
+            project.info.author.as_deref(),
+            project.info.author_email.as_deref(),
+        ),
+        maintainers: people::parse_people(
+            project.info.maintainer.as_deref(),
+            project.info.maintainer_email.as_deref(),
+        ),
+        project_urls,
+        urls: urls::collect_urls(&project.info),
+        requires_dist: project.info.requires_dist.clone().unwrap_or_default(),
+        requires_python: project.info.requires_python.clone(),
+        classifiers: project.info.classifiers.clone(),
+        supported_python_versions: python_versions::supported_versions(
+            project.info.requires_python.as_deref(),
+            &project.info.classifiers,
+        ),
+        development_status: classifiers::development_status(&project.info.classifiers),
+        typed: Some(classifiers::is_typed(&project.info.classifiers)),
+        files: None,
+        platform_coverage: None,
+        release_stats: None,
+        yanked: project.info.yanked,
+        yanked_reason: project.info.yanked_reason.clone(),
+        vulnerabilities: project.vulnerabilities.clone().unwrap_or_default(),
+        name_suspicion: None,
+        risk_factors: vec![],
         has_github_actions: None,
         has_gitlab_pipeline: None,
         has_dependabot: None,
+        clone_outcome: None,
+        ci_systems: None,
+        provenance: None,
+        activity: None,
         has_pyproject_toml: None,
         has_setup_py: None,
         has_setup_cfg: None,
     };
     my_project.process_urls(&project);
+    my_project.process_releases(&project);
+    if let Some(urls) = &project.urls {
+        let files: Vec<DistributionFile> =
+            urls.iter().map(DistributionFile::from_url_info).collect();
+        my_project.platform_coverage = PlatformCoverage::from_files(&files);
+        my_project.files = Some(files);
+    }
     debug!("Project Name: {}", project.info.name);
     debug!("Version: {}", project.info.version);
     if let Some(author) = &project.info.author {
@@ -303,7 +461,7 @@ fn analyze_project_json_from_pypi(
         debug!("Download URL: {}", download_url);
     }
 
-    my_project
+    Ok(my_project)
 }
 
 pub fn parse_rss_from_str(rss_str: &str) -> Result<Channel, Box<dyn std::error::Error>> {
@@ -317,93 +475,137 @@ pub fn get_rss() -> Result<String, Box<dyn std::error::Error>> {
     Ok(response)
 }
 
-fn handle_vcs(project: &mut MyProject) {
-    let temp_folder = tempfile::tempdir().unwrap();
-
-    if project.repository.is_none() {
+fn handle_vcs(project: &mut MyProject, config: &CollectConfig) {
+    // Local repositories come from the operator only, never from the metadata of the project
+    if let Some(local) = config
+        .local_repositories
+        .get(&normalize_package_name(&project.name))
+    {
+        project.canonical_repository = Some(local.clone());
+    } else if project.canonical_repository.is_none()
+        && let Some(url) = &project.repository
+    {
+        // Self-hosted instances are only known from the configuration
+        project.canonical_repository = config.vcs_hosts.canonicalize(url);
+    }
+    let Some(canonical) = project.canonical_repository.clone() else {
+        if let Some(url) = &project.repository {
+            debug!("Project {} uses other VCS host: {}", project.name, url);
+        }
+        return;
+    };
+    info!(
+        "Project {} uses {}: {}",
+        project.name, canonical.platform, canonical.url
+    );
+    if canonical.platform == "launchpad" {
+        // Launchpad projects have no owner, the URL does not fit git_digger
+        debug!("Not cloning Launchpad project {}", project.name);
         return;
     }
-    let repo_url = project.repository.clone().unwrap();
-    match Repository::from_url(&repo_url) {
-        Ok(repo) => {
-            let mut cloned = false;
-            let root = std::path::Path::new(temp_folder.path());
-            if repo.is_github() {
-                info!("Project {} uses GitHub.", project.name);
-                project.has_github_actions = Some(false);
-                project.has_dependabot = Some(false);
-                if repo.check_url() {
-                    info!(
-                        "Verified GitHub repository URL for project {}: {}",
-                        project.name, repo_url
-                    );
-                    cloned = repo.update_repository(root, true, Some(1)).is_ok();
-                    if repo.has_github_actions(root) {
-                        info!("Project {} has GitHub Actions configured.", project.name);
-                        project.has_github_actions = Some(true);
-                    } else {
-                        info!(
-                            "Project {} does not have GitHub Actions configured.",
-                            project.name
-                        );
-                    }
-                    if repo.has_dependabot(root) {
-                        info!("Project {} has Dependabot configured.", project.name);
-                        project.has_dependabot = Some(true);
-                    }
-                } else {
-                    error!(
-                        "GitHub Repository URL {} for project {} does not respond well.",
-                        project.name, repo_url
-                    );
-                }
-            } else if repo.is_gitlab() {
-                info!("Project {} uses GitLab.", project.name);
-                project.has_gitlab_pipeline = Some(false);
-                if repo.check_url() {
-                    info!(
-                        "Verified GitLab repository URL for project {}: {}",
-                        project.name, repo_url
-                    );
-                    cloned = repo.update_repository(root, true, Some(1)).is_ok();
-                    if repo.has_gitlab_pipeline(root) {
-                        info!("Project {} has GitLab pipeline configured.", project.name);
-                        project.has_gitlab_pipeline = Some(true);
-                    } else {
-                        info!(
-                            "Project {} does not have GitLab pipeline configured.",
-                            project.name
-                        );
-                    }
-                } else {
-                    error!(
-                        "GitLab Repository URL {} for project {} does not respond well.",
-                        project.name, repo_url
-                    );
-                }
-            } else {
-                debug!("Project {} uses other VCS host.", project.name);
-            }
 
-            if cloned {
-                let path = repo.path(root);
-                let file = path.join("pyproject.toml");
-                project.has_pyproject_toml = Some(file.exists());
-                let file = path.join("setup.py");
-                project.has_setup_py = Some(file.exists());
-                let file = path.join("setup.cfg");
-                project.has_setup_cfg = Some(file.exists());
-            }
+    if canonical.platform == "local" {
+        let path = canonical.url.trim_start_matches("file://");
+        if !Path::new(path).exists() {
+            error!(
+                "Local repository {} of project {} does not exist.",
+                path, project.name
+            );
+            project.clone_outcome = Some(String::from("not-found"));
+            return;
+        }
+    }
+
+    // git tells apart the missing repositories and the ones needing authentication, within the time limit
+    let path = match config.clone_cache.checkout(&canonical) {
+        Ok(Some(path)) => path,
+        Ok(None) => {
+            debug!("Repository {} is not in the clone cache", canonical.url);
+            return;
         }
         Err(e) => {
-            error!("Error detecting VCS host from URL '{}': {}", repo_url, e);
+            error!("Error cloning {}: {}", canonical.url, e);
+            project.clone_outcome = Some(e.outcome().to_string());
+            return;
+        }
+    };
+    project.clone_outcome = Some(String::from("ok"));
+    analyze_repository(project, &canonical.platform, &path);
+
+    let mut provenance = Provenance::check(&project.name, &path, canonical.subdirectory.as_deref());
+    // Listing the tags needs the network, except for local repositories
+    if canonical.platform == "local" || !config.clone_cache.is_no_clone() {
+        match config.clone_cache.tags(&path) {
+            Ok(tags) => {
+                provenance.tags_checked = true;
+                provenance.tag = tags::matching_tag(&tags, &project.name, &project.version);
+            }
+            Err(e) => error!("Error listing the tags of {}: {}", canonical.url, e),
+        }
+    }
+    if let Some(tag) = &provenance.tag {
+        match config.clone_cache.tag_history(&path, tag) {
+            Ok(history) => {
+                provenance.set_tag_date(history.commit_date, project.pub_date);
+                provenance.commits_since_tag = history.commits_since;
+            }
+            Err(e) => error!("Error fetching tag {} of {}: {}", tag, canonical.url, e),
+        }
+    } else if provenance.tags_checked {
+        info!(
+            "No tag of version {} of project {} in {}",
+            project.version, project.name, canonical.url
+        );
+    }
+    if provenance.is_name_mismatch() {
+        warn!(
+            "Repository {} of project {} defines other packages: {:?}",
+            canonical.url, project.name, provenance.declared_names
+        );
+    }
+    project.provenance = Some(provenance);
+
+    if config.clone_cache.has_history() {
+        match config.clone_cache.log(&path) {
+            Ok(log) => project.activity = RepositoryActivity::from_log(&log, Utc::now()),
+            Err(e) => error!("Error reading the history of {}: {}", canonical.url, e),
         }
     }
 }
 
+/// Look at the files of the repository checked out at path
+fn analyze_repository(project: &mut MyProject, platform: &str, path: &Path) {
+    let ci_systems = ci::ci_systems(platform, path);
+    if !ci_systems.is_empty() {
+        info!(
+            "Project {} has CI configured: {}",
+            project.name,
+            ci_systems.join(", ")
+        );
+    }
+    if platform == "github" {
+        project.has_github_actions =
+            Some(ci_systems.iter().any(|system| system == "github-actions"));
+        project.has_dependabot = Some(
+            path.join(".github/dependabot.yml").exists()
+                || path.join(".github/dependabot.yaml").exists(),
+        );
+    }
+    if platform == "gitlab" {
+        project.has_gitlab_pipeline = Some(ci_systems.iter().any(|system| system == "gitlab-ci"));
+    }
+    project.ci_systems = Some(ci_systems);
+
+    project.has_pyproject_toml = Some(path.join("pyproject.toml").exists());
+    project.has_setup_py = Some(path.join("setup.py").exists());
+    project.has_setup_cfg = Some(path.join("setup.cfg").exists());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clones::{CloneLimits, DEFAULT_CACHE_SIZE_MB};
+    use crate::fixtures;
 
     #[test]
     fn test_analyze_project_json_from_pypi_with_pixelcore() {
@@ -411,7 +613,7 @@ mod tests {
             .expect("Failed to read examples/pixelcore-0.0.5.json");
 
         let pub_date = Utc::now();
-        let my_project = analyze_project_json_from_pypi(&json_content, pub_date);
+        let my_project = analyze_project_json_from_pypi(&json_content, Some(pub_date)).unwrap();
 
         assert_eq!(my_project.name, "pixelcore");
         assert_eq!(my_project.version, "0.0.5");
@@ -443,7 +645,7 @@ mod tests {
             .expect("Failed to read examples/flask-3.1.2.json");
 
         let pub_date = Utc::now();
-        let my_project = analyze_project_json_from_pypi(&json_content, pub_date);
+        let my_project = analyze_project_json_from_pypi(&json_content, Some(pub_date)).unwrap();
 
         assert_eq!(my_project.name, "Flask");
         assert_eq!(my_project.version, "3.1.2");
@@ -484,6 +686,81 @@ mod tests {
                 ),
             ])
         );
+        assert_eq!(
+            my_project.development_status,
+            Some(String::from("5 - Production/Stable"))
+        );
+        assert_eq!(my_project.typed, Some(true));
+        assert_eq!(
+            my_project.supported_python_versions,
+            vec!["3.9", "3.10", "3.11", "3.12", "3.13", "3.14", "3.15"]
+        );
+        let files = my_project.files.as_ref().unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].filename, "flask-3.1.2-py3-none-any.whl");
+        assert_eq!(files[0].size, Some(103308));
+        assert_eq!(
+            files[0].sha256,
+            Some(String::from(
+                "ca1d8112ec8a6158cc29ea4858963350011b5c846a414cdb7a954aa9e967d03c"
+            ))
+        );
+        assert_eq!(
+            files[0].upload_time.map(|time| time.timestamp()),
+            Some(1755637399)
+        );
+        assert!(files[1].is_sdist());
+        assert!(!files[1].yanked);
+        assert_eq!(my_project.platform_coverage, None);
+        let release_stats = my_project.release_stats.as_ref().unwrap();
+        assert_eq!(release_stats.release_count, 63);
+        assert_eq!(
+            release_stats.first_release,
+            DateTime::parse_from_rfc3339("2010-04-16T14:29:37.458396Z").unwrap()
+        );
+        assert!(release_stats.yanked_versions.is_empty());
+        assert_eq!(my_project.yanked, Some(false));
+        assert_eq!(my_project.yanked_reason, None);
+        assert!(my_project.vulnerabilities.is_empty());
+        assert_eq!(
+            my_project.repository,
+            Some(String::from("https://github.com/pallets/flask/"))
+        );
+        assert_eq!(
+            my_project.repository_source,
+            Some(String::from("project_urls.source"))
+        );
+        assert_eq!(my_project.repository_candidates.len(), 1);
+        assert_eq!(
+            my_project.repository_url(),
+            Some(String::from("https://github.com/pallets/flask"))
+        );
+        assert_eq!(
+            my_project
+                .urls
+                .iter()
+                .filter(|url| url.kind == "repository")
+                .map(|url| url.url.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["https://github.com/pallets/flask/"]
+        );
+        let description = my_project.description.as_ref().unwrap();
+        assert_eq!(description.declared_format, "markdown");
+        assert_eq!(description.detected_format, "markdown");
+        assert!(!description.looks_broken());
+        assert_eq!(my_project.requires_dist.len(), 9);
+        assert_eq!(
+            my_project.dependency_names(),
+            vec![
+                "blinker",
+                "click",
+                "importlib-metadata",
+                "itsdangerous",
+                "jinja2",
+                "markupsafe",
+                "werkzeug"
+            ]
+        );
     }
 
     #[test]
@@ -492,12 +769,26 @@ mod tests {
             .expect("Failed to read examples/jinja-1.2.json");
 
         let pub_date = Utc::now();
-        let my_project = analyze_project_json_from_pypi(&json_content, pub_date);
+        let my_project = analyze_project_json_from_pypi(&json_content, Some(pub_date)).unwrap();
 
         assert_eq!(my_project.name, "Jinja");
         assert_eq!(my_project.version, "1.2");
         assert_eq!(my_project.pub_date, pub_date);
         assert_eq!(my_project.license, Some(String::from("BSD")));
+        assert_eq!(my_project.authors.len(), 1);
+        assert_eq!(
+            my_project.authors[0].email_domain,
+            Some(String::from("active-4.com"))
+        );
+        assert!(my_project.maintainers.is_empty());
+        assert_eq!(
+            my_project.repository_source,
+            Some(String::from("project_urls.homepage"))
+        );
+        let description = my_project.description.as_ref().unwrap();
+        assert_eq!(description.declared_format, "rst");
+        assert_eq!(description.detected_format, "rst");
+        assert!(!description.looks_broken());
         assert_eq!(my_project.license_expression, None);
         assert_eq!(
             my_project.summary,
@@ -517,6 +808,77 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_risk_factors_with_pixelcore() {
+        let json_content = fs::read_to_string("examples/pixelcore-0.0.5.json")
+            .expect("Failed to read examples/pixelcore-0.0.5.json");
+
+        let my_project = analyze_project_json_from_pypi(&json_content, None).unwrap();
+        let factors: Vec<String> = risk::risk_factors(&my_project)
+            .into_iter()
+            .map(|factor| factor.name)
+            .collect();
+        assert_eq!(factors, vec!["tiny-description"]);
+    }
+
+    #[test]
+    fn test_ci_systems_of_projects_saved_earlier() {
+        let json_content = fs::read_to_string("examples/pixelcore-0.0.5.json").unwrap();
+        let mut my_project = analyze_project_json_from_pypi(&json_content, None).unwrap();
+        assert_eq!(my_project.ci_systems(), None);
+
+        my_project.has_github_actions = Some(false);
+        assert_eq!(my_project.ci_systems(), Some(vec![]));
+
+        my_project.has_github_actions = Some(true);
+        assert_eq!(
+            my_project.ci_systems(),
+            Some(vec![String::from("github-actions")])
+        );
+
+        my_project.ci_systems = Some(vec![String::from("tox")]);
+        assert_eq!(my_project.ci_systems(), Some(vec![String::from("tox")]));
+    }
+
+    #[test]
+    fn test_analyze_project_json_from_pypi_uses_upload_time() {
+        let json_content = fs::read_to_string("examples/flask-3.1.2.json")
+            .expect("Failed to read examples/flask-3.1.2.json");
+
+        let my_project = analyze_project_json_from_pypi(&json_content, None).unwrap();
+
+        assert_eq!(
+            my_project.pub_date,
+            DateTime::parse_from_rfc3339("2025-08-19T21:03:19.499263Z").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_split_name_version() {
+        assert_eq!(
+            split_name_version("flask"),
+            (String::from("flask"), String::new())
+        );
+        assert_eq!(
+            split_name_version("flask==3.1.2"),
+            (String::from("flask"), String::from("3.1.2"))
+        );
+        assert_eq!(
+            split_name_version(" flask == 3.1.2 "),
+            (String::from("flask"), String::from("3.1.2"))
+        );
+    }
+
+    #[test]
+    fn test_read_projects_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("projects.txt");
+        fs::write(&path, "# projects\nflask\n\n  jinja==1.2 \n").unwrap();
+
+        let projects = read_projects_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(projects, vec!["flask", "jinja==1.2"]);
+    }
+
     #[test]
     fn test_extract_name_version() {
         assert_eq!(
@@ -540,4 +902,150 @@ mod tests {
         let path_short = get_pypi_project_path("ab");
         assert!(!path_short.contains("/ab/"));
     }
+
+    #[test]
+    fn test_handle_vcs_with_local_repository() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let origin = temp_folder.path().join("mirrors/pixelcore.git");
+        fixtures::create_repository(
+            &origin,
+            &[
+                ("pyproject.toml", "[project]\nname = \"PixelCore\"\n"),
+                (".github/workflows/test.yml", "on: push\n"),
+                (".woodpecker.yml", "steps: []\n"),
+            ],
+        );
+        let json_content = fs::read_to_string("examples/pixelcore-0.0.5.json").unwrap();
+        let mut project = analyze_project_json_from_pypi(&json_content, None).unwrap();
+
+        let mut config = CollectConfig {
+            clone_cache: CloneCache::new(
+                &temp_folder.path().join("clones"),
+                DEFAULT_CACHE_SIZE_MB,
+                true,
+                CloneLimits::default(),
+            )
+            .with_history(true),
+            local_repositories: local_repositories(&[format!(
+                "PixelCore=file://{}",
+                origin.display()
+            )]),
+            ..CollectConfig::default()
+        };
+        handle_vcs(&mut project, &config);
+
+        assert_eq!(
+            project
+                .canonical_repository
+                .as_ref()
+                .map(|canonical| canonical.platform.as_str()),
+            Some("local")
+        );
+        assert_eq!(project.clone_outcome, Some(String::from("ok")));
+        assert_eq!(
+            project.ci_systems,
+            Some(vec![
+                String::from("github-actions"),
+                String::from("woodpecker")
+            ])
+        );
+        assert_eq!(project.has_github_actions, None);
+        assert_eq!(project.has_pyproject_toml, Some(true));
+        assert_eq!(project.has_setup_py, Some(false));
+        let provenance = project.provenance.as_ref().unwrap();
+        assert_eq!(provenance.name_matches, Some(true));
+        assert!(provenance.tags_checked);
+        assert_eq!(provenance.tag, None);
+
+        fixtures::git(&origin, &["tag", "v0.0.5"]);
+        fixtures::commit(
+            &origin,
+            &[("pyproject.toml", "[project]\nname = \"other\"\n")],
+            "copied",
+        );
+        handle_vcs(&mut project, &config);
+        let provenance = project.provenance.as_ref().unwrap();
+        assert!(provenance.is_name_mismatch());
+        assert_eq!(provenance.tag, Some(String::from("v0.0.5")));
+        assert_eq!(provenance.commits_since_tag, Some(1));
+        let activity = project.activity.as_ref().unwrap();
+        assert_eq!(activity.commits, 2);
+        assert_eq!(activity.recent_commits, 2);
+        assert_eq!(activity.authors, 1);
+        // The fixture was tagged long after pixelcore was uploaded
+        assert!(provenance.tag_lag_hours.unwrap() < 0);
+
+        config.local_repositories = local_repositories(&[format!(
+            "pixelcore={}",
+            temp_folder.path().join("missing").display()
+        )]);
+        handle_vcs(&mut project, &config);
+        assert_eq!(project.clone_outcome, Some(String::from("not-found")));
+    }
+
+    #[test]
+    fn test_handle_vcs_ignores_local_urls_in_the_metadata() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let origin = temp_folder.path().join("origin/pixelcore");
+        fixtures::create_repository(&origin, &[("README.md", "")]);
+        let json_content = fs::read_to_string("examples/pixelcore-0.0.5.json").unwrap();
+        let mut project = analyze_project_json_from_pypi(&json_content, None).unwrap();
+        project.repository = Some(format!("file://{}", origin.display()));
+        project.canonical_repository = None;
+
+        let config = CollectConfig {
+            clone_cache: CloneCache::new(
+                &temp_folder.path().join("clones"),
+                DEFAULT_CACHE_SIZE_MB,
+                false,
+                CloneLimits::default(),
+            ),
+            ..CollectConfig::default()
+        };
+        handle_vcs(&mut project, &config);
+        assert_eq!(project.canonical_repository, None);
+        assert_eq!(project.clone_outcome, None);
+        assert!(!temp_folder.path().join("clones").exists());
+    }
+
+    #[test]
+    fn test_handle_vcs_without_clone_leaves_the_flags_unset() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let json_content = fs::read_to_string("examples/pixelcore-0.0.5.json").unwrap();
+        let mut project = analyze_project_json_from_pypi(&json_content, None).unwrap();
+        assert_eq!(
+            project
+                .canonical_repository
+                .as_ref()
+                .map(|canonical| canonical.platform.as_str()),
+            Some("github")
+        );
+
+        let config = CollectConfig {
+            clone_cache: CloneCache::new(
+                &temp_folder.path().join("clones"),
+                DEFAULT_CACHE_SIZE_MB,
+                true,
+                CloneLimits::default(),
+            ),
+            ..CollectConfig::default()
+        };
+        handle_vcs(&mut project, &config);
+        assert_eq!(project.clone_outcome, None);
+        assert_eq!(project.has_github_actions, None);
+        assert_eq!(project.has_dependabot, None);
+        assert_eq!(project.ci_systems, None);
+    }
+
+    #[test]
+    fn test_local_repositories() {
+        let repositories = local_repositories(&[
+            String::from("My_Tool=file:///srv/git/my-tool.git"),
+            String::from("other=relative/path"),
+            String::from("/srv/git/no-name"),
+            String::from("=/srv/git/empty-name"),
+        ]);
+        assert_eq!(repositories.len(), 1);
+        assert_eq!(repositories["my-tool"].url, "file:///srv/git/my-tool.git");
+    }
 }
@@ -0,0 +1,40 @@
+// Git repositories created on the fly, so the repository analysis can be tested offline
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Run git in the repository with a fixed identity, panic if it fails
+pub fn git(path: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.org"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Write the files and commit them
+pub fn commit(path: &Path, files: &[(&str, &str)], message: &str) {
+    for (name, content) in files {
+        let file = path.join(name);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(file, content).unwrap();
+    }
+    git(path, &["add", "--all"]);
+    git(path, &["commit", "--quiet", "--allow-empty", "-m", message]);
+}
+
+/// A new repository with a single commit of the files
+pub fn create_repository(path: &Path, files: &[(&str, &str)]) {
+    fs::create_dir_all(path).unwrap();
+    git(path, &["init", "--quiet"]);
+    commit(path, files, "first");
+}
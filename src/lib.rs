@@ -5,30 +5,118 @@ use chrono::serde::ts_seconds;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+pub mod activity;
+pub mod ci;
+pub mod classifiers;
+pub mod description;
+pub mod distributions;
+pub mod people;
+pub mod provenance;
+pub mod python_versions;
+pub mod releases;
+pub mod repository;
+pub mod requirements;
+pub mod risk;
+pub mod tags;
+pub mod typosquatting;
+pub mod urls;
+pub mod versions;
+pub mod vulnerabilities;
+pub mod wheels;
+
+use activity::{ActivityReport, RepositoryActivity};
+use classifiers::ClassifierReport;
+use description::{DescriptionAnalysis, DescriptionReport};
+use distributions::{DistributionFile, DistributionReport};
+use people::{PeopleReport, Person};
+use provenance::{Provenance, ProvenanceReport};
+use python_versions::PythonReport;
+use releases::{ReleaseReport, ReleaseStats};
+use repository::{CanonicalRepository, RepositoryCandidate, RepositoryReport};
+use requirements::{Requirement, RequirementsReport};
+use risk::{RiskFactor, RiskReport};
+use typosquatting::{NameSuspicion, TyposquattingReport};
+use urls::{DomainReport, ProjectUrl};
+use versions::VersionReport;
+use vulnerabilities::{Vulnerability, VulnerabilityReport};
+use wheels::{PlatformCoverage, WheelReport};
+
 pub const PAGE_SIZE: usize = 50;
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
 pub struct MyProject {
     pub name: String,
     pub version: String,
+    /// The version we had stored before this one, if it was different
+    pub previous_version: Option<String>,
     pub summary: Option<String>,
+    /// The number of characters in the long description
+    pub description_length: Option<usize>,
+    pub description: Option<DescriptionAnalysis>,
     pub license: Option<String>,
     pub license_expression: Option<String>,
     pub home_page: Option<String>,
     pub home_page_source: Option<String>,
     pub maintainer: Option<String>,
+    pub maintainer_email: Option<String>,
     pub author: Option<String>,
+    pub author_email: Option<String>,
+    /// The people parsed from the author and author_email fields
+    #[serde(default)]
+    pub authors: Vec<Person>,
+    /// The people parsed from the maintainer and maintainer_email fields
+    #[serde(default)]
+    pub maintainers: Vec<Person>,
     pub repository: Option<String>,
     pub repository_source: Option<String>,
+    /// Every place the repository URL might be, the most reliable first
+    #[serde(default)]
+    pub repository_candidates: Vec<RepositoryCandidate>,
+    /// The repository URL normalized to https://host/owner/repo
+    pub canonical_repository: Option<CanonicalRepository>,
     pub download: Option<String>,
     pub download_source: Option<String>,
 
     #[serde(with = "ts_seconds")]
     pub pub_date: DateTime<Utc>,
     pub project_urls: HashMap<String, String>,
+    /// Every URL in the metadata and in the description, classified by host
+    #[serde(default)]
+    pub urls: Vec<ProjectUrl>,
+    #[serde(default)]
+    pub requires_dist: Vec<String>,
+    pub requires_python: Option<String>,
+    #[serde(default)]
+    pub classifiers: Vec<String>,
+    /// See python_versions::supported_versions
+    #[serde(default)]
+    pub supported_python_versions: Vec<String>,
+    pub development_status: Option<String>,
+    pub typed: Option<bool>,
+    pub files: Option<Vec<DistributionFile>>,
+    pub platform_coverage: Option<PlatformCoverage>,
+    pub release_stats: Option<ReleaseStats>,
+    pub yanked: Option<bool>,
+    pub yanked_reason: Option<String>,
+    /// The known vulnerabilities of this release, including the withdrawn ones
+    #[serde(default)]
+    pub vulnerabilities: Vec<Vulnerability>,
+    /// The name looks like the name of a popular package
+    pub name_suspicion: Option<NameSuspicion>,
+    /// The signals that make the project worth a look, see risk::risk_factors
+    #[serde(default)]
+    pub risk_factors: Vec<RiskFactor>,
     pub has_github_actions: Option<bool>,
     pub has_gitlab_pipeline: Option<bool>,
     pub has_dependabot: Option<bool>,
+    /// ok, timeout, too-large, auth-required, not-found or failed
+    pub clone_outcome: Option<String>,
+    /// The CI systems configured in the repository, see ci::ci_systems
+    pub ci_systems: Option<Vec<String>>,
+    /// Does the repository contain the package, see provenance::Provenance::check
+    pub provenance: Option<Provenance>,
+    /// Only measured when we keep the whole history of the repositories
+    pub activity: Option<RepositoryActivity>,
 
     pub has_pyproject_toml: Option<bool>,
     pub has_setup_py: Option<bool>,
@@ -53,7 +141,16 @@ pub struct MyFilteredProject {
 
 #[derive(Debug, Serialize)]
 pub struct VCSReport {
+    /// Number of projects on each platform: github, gitlab, codeberg, bitbucket, sourcehut, gitea,
+    /// launchpad or local
     pub hosts: HashMap<String, u32>,
+    /// Number of projects using each CI system, see ci::ci_systems
+    pub ci_systems: HashMap<String, u32>,
+    /// Projects whose repository we checked and found no CI configuration in
+    pub no_ci_count: u32,
+    pub no_ci: Vec<MyFilteredProject>,
+    /// Number of projects with each outcome of cloning the repository
+    pub clone_outcomes: HashMap<String, u32>,
     pub no_vcs_count: u32,
     pub no_vcs: Vec<MyFilteredProject>,
     pub bad_vcs_count: u32,
@@ -62,18 +159,10 @@ pub struct VCSReport {
     pub github_projects: Vec<MyFilteredProject>,
     pub gitlab_count: u32,
     pub gitlab_projects: Vec<MyFilteredProject>,
-    pub no_github_actions_count: u32,
-    pub no_github_actions: Vec<MyFilteredProject>,
-    pub has_github_actions_count: u32,
-    pub has_github_actions: Vec<MyFilteredProject>,
     pub no_dependabot_count: u32,
     pub no_dependabot: Vec<MyFilteredProject>,
     pub has_dependabot_count: u32,
     pub has_dependabot: Vec<MyFilteredProject>,
-    pub has_gitlab_pipeline_count: u32,
-    pub has_gitlab_pipeline: Vec<MyFilteredProject>,
-    pub no_gitlab_pipeline_count: u32,
-    pub no_gitlab_pipeline: Vec<MyFilteredProject>,
     pub has_setup_cfg_count: u32,
     pub has_setup_cfg: Vec<MyFilteredProject>,
     pub no_pyproject_toml_count: u32,
@@ -91,12 +180,45 @@ pub struct LicenseReport {
     pub long_license: Vec<MyFilteredProject>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct DependencyCount {
+    pub name: String,
+    pub count: u32,
+    /// The dependency is itself one of the collected projects
+    pub collected: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyReport {
+    pub distinct_dependencies: usize,
+    pub most_depended_upon: Vec<DependencyCount>,
+    pub no_dependencies_count: u32,
+    pub no_dependencies: Vec<MyFilteredProject>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct Report {
     pub total: usize,
     pub projects: Vec<MyFilteredProject>,
     pub license: LicenseReport,
     pub vcs: VCSReport,
+    pub dependencies: DependencyReport,
+    pub requirements: RequirementsReport,
+    pub python: PythonReport,
+    pub classifiers: ClassifierReport,
+    pub distributions: DistributionReport,
+    pub wheels: WheelReport,
+    pub releases: ReleaseReport,
+    pub versions: VersionReport,
+    pub vulnerabilities: VulnerabilityReport,
+    pub people: PeopleReport,
+    pub typosquatting: TyposquattingReport,
+    pub risk: RiskReport,
+    pub description: DescriptionReport,
+    pub domains: DomainReport,
+    pub repository: RepositoryReport,
+    pub provenance: ProvenanceReport,
+    pub activity: ActivityReport,
     pub project_urls_count: HashMap<String, u32>,
 }
 
@@ -110,110 +232,205 @@ impl MyProject {
         }
     }
 
+    /// The normalized names of the packages listed in requires_dist.
+    /// Requirements that only apply to an extra (e.g. `; extra == "dev"`) are skipped
+    /// as they are not needed to install the project.
+    pub fn dependency_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .requires_dist
+            .iter()
+            .filter_map(|req| requirements::dependency_name(req))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// The sum of the weights of the risk factors
+    pub fn risk_score(&self) -> u32 {
+        self.risk_factors.iter().map(|factor| factor.weight).sum()
+    }
+
+    /// The authors and the maintainers, each person listed once
+    pub fn people(&self) -> Vec<Person> {
+        people::dedup(
+            self.authors
+                .iter()
+                .chain(self.maintainers.iter())
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// The parsed entries of requires_dist, entries that cannot be parsed are skipped
+    pub fn requirements(&self) -> Vec<Requirement> {
+        self.requires_dist
+            .iter()
+            .filter_map(|req| Requirement::parse(req))
+            .collect()
+    }
+
     // See https://packaging.python.org/en/latest/specifications/well-known-project-urls/
-    // TODO: Where does the project store the VCS URL?
-    // There can be several names in project_urls and some use the home_page field for that.
-    // We should report if the porject uses the "old way" or if it uses multiple ways.
-    // For now let's check several
-
-    // TODO
-    // Report if we found a repository URL in more than one place
-    // Especially if they differ
+    // The repository URL can be in several of the project_urls and some projects use
+    // the legacy home_page field for it. We keep all of them as candidates and pick
+    // the one with the highest priority, see repository::SOURCES.
     pub fn process_urls(&mut self, project: &PyPiProject) {
-        match &project.info.project_urls {
-            Some(urls) => {
-                for (key, value) in urls.iter() {
-                    if let Some(value_str) = value.as_str() {
-                        self.project_urls.insert(key.clone(), value_str.to_string());
-                        let normalized_key = normalize_url(key);
-
-                        if normalized_key == "source" {
-                            self.repository = Some(value_str.to_string());
-                            self.repository_source = Some(String::from("project_urls.source"));
-                        }
-                        if normalized_key == "sourcecode" {
-                            self.repository = Some(value_str.to_string());
-                            self.repository_source = Some(String::from("project_urls.sourcecode"));
-                        }
-                        if normalized_key == "repository" {
-                            self.repository = Some(value_str.to_string());
-                            self.repository_source = Some(String::from("project_urls.repository"));
-                        }
-                        if normalized_key == "github" {
-                            self.repository = Some(value_str.to_string());
-                            self.repository_source = Some(String::from("project_urls.github"));
-                        }
-
-                        if normalized_key == "download" {
-                            self.download = Some(value_str.to_string());
-                            self.download_source = Some(String::from("project_urls.download"));
-                        }
-
-                        if normalized_key == "homepage" {
-                            self.home_page = Some(value_str.to_string());
-                            self.home_page_source = Some(String::from("project_urls.homepage"));
-
-                            if self.repository.is_none() {
-                                self.repository = Some(value_str.to_string());
-                                self.repository_source =
-                                    Some(String::from("project_urls.homepage"));
-                            }
-                        }
+        let mut candidates: Vec<RepositoryCandidate> = vec![];
+        if let Some(urls) = &project.info.project_urls {
+            // Sort the keys so the result does not depend on the order in the JSON
+            let mut keys: Vec<&String> = urls.keys().collect();
+            keys.sort();
+            for key in keys {
+                if let Some(value_str) = urls[key].as_str() {
+                    self.project_urls.insert(key.clone(), value_str.to_string());
+                    let normalized_key = normalize_url(key);
+
+                    if let Some(source) = repository::project_urls_source(&normalized_key) {
+                        candidates.push(RepositoryCandidate::new(value_str, source));
+                    }
+
+                    if normalized_key == "download" && self.download.is_none() {
+                        self.download = Some(value_str.to_string());
+                        self.download_source = Some(String::from("project_urls.download"));
+                    }
+
+                    if normalized_key == "homepage" && self.home_page.is_none() {
+                        self.home_page = Some(value_str.to_string());
+                        self.home_page_source = Some(String::from("project_urls.homepage"));
                     }
                 }
             }
-            None => {}
         }
 
-        if self.home_page.is_none() {
-            match &project.info.home_page {
-                Some(home_page) => {
-                    self.home_page = Some(home_page.clone());
-                    self.home_page_source = Some(String::from("info.home_page"));
-                }
-                None => {}
+        if let Some(home_page) = &project.info.home_page {
+            candidates.push(RepositoryCandidate::new(home_page, "info.home_page"));
+            if self.home_page.is_none() {
+                self.home_page = Some(home_page.clone());
+                self.home_page_source = Some(String::from("info.home_page"));
             }
         }
-        if self.download.is_none() {
-            match &project.info.download_url {
-                Some(download_url) => {
-                    self.download = Some(download_url.clone());
-                    self.download_source = Some(String::from("info.download_url"));
-                }
-                None => {}
-            }
+        if self.download.is_none()
+            && let Some(download_url) = &project.info.download_url
+        {
+            self.download = Some(download_url.clone());
+            self.download_source = Some(String::from("info.download_url"));
         }
 
-        if self.repository.is_none() {
-            if !self.home_page.is_none() {
-                self.repository = self.home_page.clone();
-                self.repository_source = Some(String::from("info.home_page"));
-            }
-        };
+        repository::sort_candidates(&mut candidates);
+        if let Some(best) = candidates.first() {
+            self.repository = Some(best.url.clone());
+            self.repository_source = Some(best.source.clone());
+        }
+        self.repository_candidates = candidates;
+        self.canonical_repository = self
+            .repository
+            .as_deref()
+            .and_then(repository::canonicalize);
+    }
+
+    /// The CI systems of the repository, None if we did not look at it.
+    /// Projects saved before we stored the list only know about GitHub Actions and GitLab CI.
+    pub fn ci_systems(&self) -> Option<Vec<String>> {
+        if self.ci_systems.is_some() {
+            return self.ci_systems.clone();
+        }
+        let known = [
+            ("github-actions", self.has_github_actions),
+            ("gitlab-ci", self.has_gitlab_pipeline),
+        ];
+        if known.iter().all(|(_, found)| found.is_none()) {
+            return None;
+        }
+        Some(
+            known
+                .iter()
+                .filter(|(_, found)| *found == Some(true))
+                .map(|(system, _)| system.to_string())
+                .collect(),
+        )
+    }
+
+    /// The canonical repository URL if we could normalize it, the raw one otherwise.
+    /// Projects saved before we stored the canonical form are normalized here.
+    pub fn repository_url(&self) -> Option<String> {
+        if let Some(canonical) = &self.canonical_repository {
+            return Some(canonical.url.clone());
+        }
+        let url = self.repository.as_ref()?;
+        Some(
+            repository::canonicalize(url)
+                .map_or_else(|| url.trim().to_string(), |canonical| canonical.url),
+        )
+    }
+
+    /// Compute the release history from the releases map.
+    /// The version specific JSON of PyPI does not contain the releases, in that case nothing is set.
+    pub fn process_releases(&mut self, project: &PyPiProject) {
+        if let Some(releases) = &project.releases {
+            let releases = releases::releases_from_map(releases);
+            self.release_stats = ReleaseStats::from_releases(&releases, &self.version);
+        }
     }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct PyPiProject {
     pub info: Info,
-    #[allow(dead_code)]
     pub urls: Option<Vec<UrlInfo>>, // If present in other samples
-    #[allow(dead_code)]
-    pub releases: Option<serde_json::Value>, // For flexibility
+    #[serde(default, deserialize_with = "lenient_releases")]
+    pub releases: Option<HashMap<String, Vec<UrlInfo>>>,
+    pub vulnerabilities: Option<Vec<Vulnerability>>,
+}
+
+impl PyPiProject {
+    /// The time the first file of the release was uploaded
+    pub fn upload_time(&self) -> Option<DateTime<Utc>> {
+        self.urls
+            .as_ref()?
+            .iter()
+            .filter_map(|url| url.upload_time_iso_8601)
+            .min()
+    }
+}
+
+/// The releases map of the PyPI JSON. The files that cannot be parsed, and the releases
+/// that are not a list of files, are skipped so a single odd entry does not fail the project.
+fn lenient_releases<'de, D>(
+    deserializer: D,
+) -> Result<Option<HashMap<String, Vec<UrlInfo>>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let Some(serde_json::Value::Object(releases)) =
+        Option::<serde_json::Value>::deserialize(deserializer)?
+    else {
+        return Ok(None);
+    };
+    Ok(Some(
+        releases
+            .into_iter()
+            .filter_map(|(version, files)| {
+                let serde_json::Value::Array(files) = files else {
+                    return None;
+                };
+                let files = files
+                    .into_iter()
+                    .filter_map(|file| serde_json::from_value(file).ok())
+                    .collect();
+                Some((version, files))
+            })
+            .collect(),
+    ))
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Info {
     pub author: Option<String>,
-    #[allow(dead_code)]
+    pub author_email: Option<String>,
     pub bugtrack_url: Option<String>,
-    #[allow(dead_code)]
     pub classifiers: Vec<String>,
-    #[allow(dead_code)]
     pub description: String,
-    #[allow(dead_code)]
     pub description_content_type: Option<String>,
-    #[allow(dead_code)]
     pub docs_url: Option<String>,
     pub download_url: Option<String>,
     pub home_page: Option<String>,
@@ -222,6 +439,7 @@ pub struct Info {
     pub license: Option<String>,
     pub license_expression: Option<String>,
     pub maintainer: Option<String>,
+    pub maintainer_email: Option<String>,
     pub name: String,
     #[allow(dead_code)]
     pub package_url: Option<String>,
@@ -233,13 +451,10 @@ pub struct Info {
     #[allow(dead_code)]
     pub release_url: Option<String>,
     pub requires_dist: Option<Vec<String>>,
-    #[allow(dead_code)]
     pub requires_python: Option<String>,
     pub summary: Option<String>,
     pub version: String,
-    #[allow(dead_code)]
     pub yanked: Option<bool>,
-    #[allow(dead_code)]
     pub yanked_reason: Option<String>,
 }
 
@@ -247,10 +462,20 @@ pub struct Info {
 pub struct UrlInfo {
     #[allow(dead_code)]
     pub url: String,
-    #[allow(dead_code)]
     pub packagetype: Option<String>,
-    #[allow(dead_code)]
     pub filename: Option<String>,
+    pub size: Option<u64>,
+    pub digests: Option<Digests>,
+    pub upload_time_iso_8601: Option<DateTime<Utc>>,
+    pub python_version: Option<String>,
+    pub requires_python: Option<String>,
+    pub yanked: Option<bool>,
+    pub yanked_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Digests {
+    pub sha256: Option<String>,
 }
 
 // As explained here: https://packaging.python.org/en/latest/specifications/well-known-project-urls/#label-normalization
@@ -269,10 +494,66 @@ fn normalize_url(url: &str) -> String {
 
     result.to_lowercase()
 }
+
+/// The lower-case host name of an http or https URL, without the user and the port
+pub fn url_host(url: &str) -> Option<String> {
+    let url = url.trim().to_lowercase();
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    if host.is_empty() {
+        return None;
+    }
+    Some(host.to_string())
+}
+
+/// FNV-1a, unlike the hasher of the standard library it is the same in every Rust version
+pub fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Normalize a package name as described in
+/// https://packaging.python.org/en/latest/specifications/name-normalization/
+pub fn normalize_package_name(name: &str) -> String {
+    let mut result = String::new();
+    let mut in_separator = false;
+    for c in name.trim().chars() {
+        if c == '-' || c == '_' || c == '.' {
+            in_separator = true;
+            continue;
+        }
+        if in_separator && !result.is_empty() {
+            result.push('-');
+        }
+        in_separator = false;
+        result.extend(c.to_lowercase());
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            url_host("https://GitHub.com/pallets/flask"),
+            Some(String::from("github.com"))
+        );
+        assert_eq!(
+            url_host("http://user@example.org:8080?q=1"),
+            Some(String::from("example.org"))
+        );
+        assert_eq!(url_host("git@github.com:pallets/flask.git"), None);
+        assert_eq!(url_host("https://"), None);
+    }
+
     #[test]
     fn test_normalize_url_empty() {
         assert_eq!(normalize_url(""), "");
@@ -318,4 +599,50 @@ mod tests {
         assert_eq!(normalize_url("abc123"), "abc123");
         assert_eq!(normalize_url("Home123Page"), "home123page");
     }
+
+    #[test]
+    fn test_stable_hash() {
+        assert_eq!(stable_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(stable_hash("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_normalize_package_name() {
+        assert_eq!(normalize_package_name("Flask"), "flask");
+        assert_eq!(
+            normalize_package_name("importlib_metadata"),
+            "importlib-metadata"
+        );
+        assert_eq!(normalize_package_name("zope.interface"), "zope-interface");
+        assert_eq!(normalize_package_name("Foo--_.Bar"), "foo-bar");
+    }
+
+    #[test]
+    fn test_lenient_releases() {
+        let json = r#"{
+            "info": {"classifiers": [], "description": "", "name": "odd", "version": "1.0"},
+            "releases": {
+                "1.0": [
+                    {"url": "https://files.example.org/odd-1.0.tar.gz", "yanked": false},
+                    {"url": 42}
+                ],
+                "0.9": {"url": "https://files.example.org/odd-0.9.tar.gz"},
+                "0.1": []
+            }
+        }"#;
+        let project = serde_json::from_str::<PyPiProject>(json).unwrap();
+        let releases = project.releases.unwrap();
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases["1.0"].len(), 1);
+        assert!(releases["0.1"].is_empty());
+
+        let json =
+            r#"{"info": {"classifiers": [], "description": "", "name": "odd", "version": "1.0"}}"#;
+        assert!(
+            serde_json::from_str::<PyPiProject>(json)
+                .unwrap()
+                .releases
+                .is_none()
+        );
+    }
 }
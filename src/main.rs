@@ -1,9 +1,16 @@
-use chrono::{DateTime, Utc};
+use std::path::Path;
+
 use clap::Parser;
 use tracing::{Level, error, info};
 use tracing_subscriber::FmtSubscriber;
 
+use pydigger::repository::VcsHosts;
+use pydigger::typosquatting::PopularNames;
+
+mod clones;
 mod download;
+#[cfg(test)]
+mod fixtures;
 mod report;
 
 /// Command line arguments
@@ -18,9 +25,51 @@ pub struct Args {
     #[arg(long)]
     pub limit: Option<usize>,
 
-    /// Name of the project to download (used mostly during development)
+    /// Names of the projects to download, NAME or NAME==VERSION (used mostly during development)
+    #[arg(long, num_args = 1..)]
+    pub project: Vec<String>,
+
+    /// File with the names of the projects to download, one NAME or NAME==VERSION per line
+    #[arg(long)]
+    pub projects_file: Option<String>,
+
+    /// File with the names of popular packages, one per line, used to detect typosquatting.
+    /// Defaults to a built-in list of the most downloaded packages.
+    #[arg(long)]
+    pub popular_packages: Option<String>,
+
+    /// Domains of self-hosted GitLab instances, e.g. gitlab.example.org
+    #[arg(long, num_args = 1..)]
+    pub gitlab_host: Vec<String>,
+
+    /// Domains of self-hosted Gitea or Forgejo instances
+    #[arg(long, num_args = 1..)]
+    pub gitea_host: Vec<String>,
+
+    /// Analyze the repository at PATH for the project NAME instead of the one in its metadata,
+    /// e.g. a mirror. PATH is absolute or a file:// URL.
+    #[arg(long, num_args = 1.., value_name = "NAME=PATH")]
+    pub local_repository: Vec<String>,
+
+    /// Don't clone or fetch repositories, analyze only the ones already in the clone cache
     #[arg(long)]
-    pub project: Option<String>,
+    pub no_clone: bool,
+
+    /// The size limit of the clone cache in MB, the least recently used clones are removed
+    #[arg(long, default_value_t = clones::DEFAULT_CACHE_SIZE_MB)]
+    pub clone_cache_size: u64,
+
+    /// Stop cloning or fetching a repository after this many seconds
+    #[arg(long, default_value_t = clones::DEFAULT_CLONE_TIMEOUT_SECS)]
+    pub clone_timeout: u64,
+
+    /// Stop cloning a repository when it grows over this many MB
+    #[arg(long, default_value_t = clones::DEFAULT_MAX_REPOSITORY_SIZE_MB)]
+    pub max_repository_size: u64,
+
+    /// Keep the whole history of the repositories to measure their activity
+    #[arg(long)]
+    pub history: bool,
 
     /// Generate a report from existing project files
     #[arg(long)]
@@ -43,24 +92,49 @@ fn main() {
     setup_logging(&args);
     info!("PyDigger started");
 
+    let popular_names = match &args.popular_packages {
+        Some(path) => PopularNames::from_file(path).unwrap_or_else(|e| {
+            error!("Error reading popular packages file '{}': {}", path, e);
+            PopularNames::default()
+        }),
+        None => PopularNames::default(),
+    };
+    let config = download::CollectConfig {
+        popular_names,
+        vcs_hosts: VcsHosts::new(&args.gitlab_host, &args.gitea_host),
+        clone_cache: clones::CloneCache::new(
+            Path::new(&clones::get_clones_path()),
+            args.clone_cache_size,
+            args.no_clone,
+            clones::CloneLimits::new(args.clone_timeout, args.max_repository_size),
+        )
+        .with_history(args.history),
+        local_repositories: download::local_repositories(&args.local_repository),
+    };
+
     if args.download {
-        let cs = download::download_project_json(&args);
+        let cs = download::download_project_json(&args, &config);
         download::save_download_stats(cs).unwrap_or_else(|e| {
             error!("Error saving download stats: {}", e);
         });
     }
-    if args.project.is_some() {
-        let name = args.project.as_ref().unwrap().clone();
-        let version = String::new();
-        let pub_date: DateTime<Utc> = Utc::now();
-        match download::handle_project(name, version, pub_date) {
-            Ok(()) => info!("Project processed successfully!"),
-            Err(e) => error!("Error processing project: {}", e),
+    let mut projects = args.project.clone();
+    if let Some(projects_file) = &args.projects_file {
+        match download::read_projects_file(projects_file) {
+            Ok(names) => projects.extend(names),
+            Err(e) => error!("Error reading projects file '{}': {}", projects_file, e),
+        }
+    }
+    for project in projects {
+        let (name, version) = download::split_name_version(&project);
+        match download::handle_project(name, version, None, &config) {
+            Ok(()) => info!("Project {} processed successfully!", project),
+            Err(e) => error!("Error processing project {}: {}", project, e),
         }
     }
 
     if args.report {
-        match report::generate_report() {
+        match report::generate_report(&config.vcs_hosts) {
             Ok(()) => info!("Report generated successfully!"),
             Err(e) => error!("Error generating report: {}", e),
         }
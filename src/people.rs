@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{MyFilteredProject, stable_hash};
+
+/// Values people put in the author and maintainer fields when they have nothing to say
+const PLACEHOLDERS: [&str; 4] = ["unknown", "none", "n/a", "-"];
+
+/// An author or maintainer of a project
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct Person {
+    /// The name with the whitespace collapsed
+    pub name: Option<String>,
+    /// The lower-case email address
+    pub email: Option<String>,
+    /// The lower-case domain of the email address
+    pub email_domain: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UploaderCount {
+    pub name: Option<String>,
+    /// Only the domain is published, not the email address
+    pub email_domain: Option<String>,
+    pub count: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PeopleReport {
+    pub distinct_people: usize,
+    /// The people who are authors or maintainers of the most collected projects
+    pub top_uploaders: Vec<UploaderCount>,
+    /// Number of projects with an author or maintainer email at each domain
+    pub email_domains: HashMap<String, u32>,
+    pub no_people_count: u32,
+    pub no_people: Vec<MyFilteredProject>,
+}
+
+impl Person {
+    fn new(name: Option<&str>, email: Option<&str>) -> Option<Person> {
+        let name = name.and_then(normalize_name);
+        let email = email.and_then(normalize_email);
+        if name.is_none() && email.is_none() {
+            return None;
+        }
+        let email_domain = email
+            .as_ref()
+            .and_then(|email| email.rsplit_once('@'))
+            .map(|(_, domain)| domain.trim_end_matches('.').to_string());
+        Some(Person {
+            name,
+            email,
+            email_domain,
+        })
+    }
+
+    /// Parse a single entry such as `Jane Doe <jane@example.org>`, `jane@example.org` or `Jane Doe`
+    fn parse_entry(entry: &str) -> Option<Person> {
+        let entry = entry.trim();
+        if let Some((name, rest)) = entry.split_once('<')
+            && let Some((email, _)) = rest.split_once('>')
+        {
+            return Person::new(Some(name), Some(email));
+        }
+        if entry.contains('@') && !entry.contains(' ') {
+            return Person::new(None, Some(entry));
+        }
+        Person::new(Some(entry), None)
+    }
+
+    /// The identity used to match the same person across projects:
+    /// the hash of the email if we have one, so the reports don't list the addresses,
+    /// the lower-case name otherwise
+    pub fn key(&self) -> String {
+        match (&self.email, &self.name) {
+            (Some(email), _) => format!("{:016x}", stable_hash(email)),
+            (None, Some(name)) => name.to_lowercase(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+fn normalize_name(name: &str) -> Option<String> {
+    let name = name.trim().trim_matches(|c| c == '"' || c == '\'');
+    let name = name.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if name.is_empty() || PLACEHOLDERS.contains(&name.to_lowercase().as_str()) {
+        return None;
+    }
+    Some(name)
+}
+
+fn normalize_email(email: &str) -> Option<String> {
+    let email = email.trim().to_lowercase();
+    let (local, domain) = email.rsplit_once('@')?;
+    if local.is_empty() || domain.is_empty() || email.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(email)
+}
+
+/// Split a list of people on commas and semicolons that are not inside quotes or angle brackets
+fn split_list(value: &str) -> Vec<&str> {
+    let mut entries = vec![];
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut in_brackets = false;
+    for (index, c) in value.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '<' => in_brackets = true,
+            '>' => in_brackets = false,
+            ',' | ';' if !in_quotes && !in_brackets => {
+                entries.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    entries.push(&value[start..]);
+    entries
+        .into_iter()
+        .filter(|entry| !entry.trim().is_empty())
+        .collect()
+}
+
+/// Parse the name and email fields of the metadata (`author` and `author_email`, or
+/// `maintainer` and `maintainer_email`) into a list of people.
+/// Both fields can hold lists and the email field often holds `Name <email>` entries.
+/// When the name field has only names and the email field has only addresses,
+/// and there are as many of each, they are paired up in order.
+pub fn parse_people(names: Option<&str>, emails: Option<&str>) -> Vec<Person> {
+    let from_names: Vec<Person> = split_list(names.unwrap_or(""))
+        .into_iter()
+        .filter_map(Person::parse_entry)
+        .collect();
+    let from_emails: Vec<Person> = split_list(emails.unwrap_or(""))
+        .into_iter()
+        .filter_map(Person::parse_entry)
+        .collect();
+
+    let pairable = !from_names.is_empty()
+        && from_names.len() == from_emails.len()
+        && from_names.iter().all(|person| person.email.is_none())
+        && from_emails.iter().all(|person| person.name.is_none());
+
+    let people: Vec<Person> = if pairable {
+        from_names
+            .into_iter()
+            .zip(from_emails)
+            .map(|(name, email)| Person {
+                name: name.name,
+                email: email.email,
+                email_domain: email.email_domain,
+            })
+            .collect()
+    } else {
+        // Drop the bare names that also appear with an email
+        let named: Vec<String> = from_emails
+            .iter()
+            .filter_map(|person| person.name.as_ref().map(|name| name.to_lowercase()))
+            .collect();
+        from_names
+            .into_iter()
+            .filter(|person| {
+                person.email.is_some()
+                    || person
+                        .name
+                        .as_ref()
+                        .is_none_or(|name| !named.contains(&name.to_lowercase()))
+            })
+            .chain(from_emails)
+            .collect()
+    };
+
+    dedup(people)
+}
+
+/// Remove the repeated people keeping the first occurrence
+pub fn dedup(people: Vec<Person>) -> Vec<Person> {
+    let mut seen: Vec<String> = vec![];
+    people
+        .into_iter()
+        .filter(|person| {
+            let key = person.key();
+            if seen.contains(&key) {
+                return false;
+            }
+            seen.push(key);
+            true
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn person(name: Option<&str>, email: Option<&str>) -> Person {
+        Person::new(name, email).unwrap()
+    }
+
+    #[test]
+    fn test_parse_name_and_email() {
+        assert_eq!(
+            parse_people(Some("Armin Ronacher"), Some("armin.ronacher@active-4.com")),
+            vec![Person {
+                name: Some(String::from("Armin Ronacher")),
+                email: Some(String::from("armin.ronacher@active-4.com")),
+                email_domain: Some(String::from("active-4.com")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_name_in_email_field() {
+        assert_eq!(
+            parse_people(None, Some("Pallets <contact@PalletsProjects.com>")),
+            vec![person(Some("Pallets"), Some("contact@palletsprojects.com"))]
+        );
+        assert_eq!(
+            parse_people(
+                Some("Pallets"),
+                Some("Pallets <contact@palletsprojects.com>")
+            ),
+            vec![person(Some("Pallets"), Some("contact@palletsprojects.com"))]
+        );
+    }
+
+    #[test]
+    fn test_parse_lists() {
+        assert_eq!(
+            parse_people(
+                None,
+                Some(r#""Doe, Jane" <jane@example.org>, John  Smith <john@example.com>"#)
+            ),
+            vec![
+                person(Some("Doe, Jane"), Some("jane@example.org")),
+                person(Some("John Smith"), Some("john@example.com")),
+            ]
+        );
+        assert_eq!(
+            parse_people(
+                Some("Jane, John"),
+                Some("jane@example.org; john@example.com")
+            ),
+            vec![
+                person(Some("Jane"), Some("jane@example.org")),
+                person(Some("John"), Some("john@example.com")),
+            ]
+        );
+        assert_eq!(
+            parse_people(Some("Jane, John"), Some("team@example.org")),
+            vec![
+                person(Some("Jane"), None),
+                person(Some("John"), None),
+                person(None, Some("team@example.org")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_placeholders() {
+        assert_eq!(parse_people(None, None), vec![]);
+        assert_eq!(parse_people(Some("UNKNOWN"), Some("")), vec![]);
+        assert_eq!(parse_people(Some(" "), Some("None")), vec![]);
+    }
+
+    #[test]
+    fn test_key() {
+        assert_eq!(
+            person(Some("Jane"), Some("jane@example.org")).key(),
+            person(None, Some("Jane@Example.org")).key()
+        );
+        assert!(
+            !person(Some("Jane"), Some("jane@example.org"))
+                .key()
+                .contains("example")
+        );
+        assert_eq!(person(Some("Jane Doe"), None).key(), "jane doe");
+    }
+}
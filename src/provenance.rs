@@ -0,0 +1,356 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{MyFilteredProject, normalize_package_name};
+
+static SETUP_PY_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)\bsetup\s*\(.*?\bname\s*=\s*["']([A-Za-z0-9._-]+)["']"#).unwrap()
+});
+
+/// The sections of pyproject.toml and setup.cfg that hold the name of the package
+const PYPROJECT_SECTIONS: [&str; 2] = ["[project]", "[tool.poetry]"];
+const SETUP_CFG_SECTIONS: [&str; 1] = ["[metadata]"];
+
+/// Does the repository contain the package published on PyPI?
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct Provenance {
+    /// The names of the packages defined in the repository
+    pub declared_names: Vec<DeclaredName>,
+    /// One of the declared names is the name of the project.
+    /// None if we could not read any name, e.g. it is computed in setup.py.
+    pub name_matches: Option<bool>,
+    /// The tag of the released version, see tags::matching_tag
+    pub tag: Option<String>,
+    /// False if we could not list the tags
+    pub tags_checked: bool,
+    /// The date of the tagged commit
+    #[serde(default, with = "ts_seconds_option")]
+    pub tag_date: Option<DateTime<Utc>>,
+    /// The release was uploaded this many hours after the tagged commit, negative if before it
+    pub tag_lag_hours: Option<i64>,
+    /// Commits on the branch after the tag. None if the tag is not on the branch we cloned.
+    pub commits_since_tag: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct DeclaredName {
+    /// The file relative to the root of the repository, e.g. `pyproject.toml` or `src/setup.cfg`
+    pub file: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MismatchedProject {
+    pub project: MyFilteredProject,
+    pub repository: String,
+    pub declared_names: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProvenanceReport {
+    /// The repository defines a package with the name of the project
+    pub verified_count: u32,
+    /// We could not read the name of the package in the repository
+    pub unknown_name_count: u32,
+    /// The repository defines other packages only, it might be unrelated to the project or a copy
+    pub name_mismatch_count: u32,
+    pub name_mismatch: Vec<MismatchedProject>,
+    pub tag_found_count: u32,
+    /// The releases without a matching tag in the repository
+    pub tag_missing_count: u32,
+    pub tag_missing: Vec<MyFilteredProject>,
+    /// Number of tagged releases by the time between the tagged commit and the upload,
+    /// see tag_lag_bucket
+    pub tag_lag: HashMap<String, u32>,
+}
+
+impl Provenance {
+    /// Compare the packages defined in the checked out repository to the project.
+    /// The root, or the subdirectory given in the URL, and the folders right below it are searched.
+    /// A subdirectory leading out of the repository is not searched, neither are the symbolic links.
+    pub fn check(name: &str, path: &Path, subdirectory: Option<&str>) -> Provenance {
+        let mut declared_names = vec![];
+        if let Some((root, base)) = search_base(path, subdirectory) {
+            declared_names = declared_names_in(&root, &base);
+            if let Ok(entries) = fs::read_dir(&base) {
+                let mut folders: Vec<_> = entries
+                    .flatten()
+                    .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+                    .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+                    .map(|entry| entry.path())
+                    .collect();
+                folders.sort();
+                for folder in folders {
+                    declared_names.extend(declared_names_in(&root, &folder));
+                }
+            }
+        }
+
+        let name = normalize_package_name(name);
+        let name_matches = (!declared_names.is_empty()).then(|| {
+            declared_names
+                .iter()
+                .any(|declared| normalize_package_name(&declared.name) == name)
+        });
+        Provenance {
+            declared_names,
+            name_matches,
+            tag: None,
+            tags_checked: false,
+            tag_date: None,
+            tag_lag_hours: None,
+            commits_since_tag: None,
+        }
+    }
+
+    /// The repository defines packages, none of them is the project
+    pub fn is_name_mismatch(&self) -> bool {
+        self.name_matches == Some(false)
+    }
+
+    /// The tag was checked and none matches the released version
+    pub fn is_tag_missing(&self) -> bool {
+        self.tags_checked && self.tag.is_none()
+    }
+
+    /// Record the date of the tagged commit and how long before the upload it was made
+    pub fn set_tag_date(&mut self, tag_date: DateTime<Utc>, pub_date: DateTime<Utc>) {
+        self.tag_date = Some(tag_date);
+        self.tag_lag_hours = Some((pub_date - tag_date).num_hours());
+    }
+}
+
+/// A release is usually uploaded soon after the commit is tagged.
+/// A commit tagged long before the upload, or after it, is worth a look.
+pub fn tag_lag_bucket(lag_hours: i64) -> &'static str {
+    match lag_hours {
+        ..0 => "tagged-after-upload",
+        0..24 => "within-a-day",
+        24..168 => "within-a-week",
+        168..720 => "within-a-month",
+        _ => "over-a-month",
+    }
+}
+
+/// The resolved root of the repository and the folder to search in it,
+/// None if the folder does not exist or is outside of the repository
+fn search_base(path: &Path, subdirectory: Option<&str>) -> Option<(PathBuf, PathBuf)> {
+    let root = path.canonicalize().ok()?;
+    let base = match subdirectory {
+        Some(subdirectory) => root.join(subdirectory).canonicalize().ok()?,
+        None => root.clone(),
+    };
+    base.starts_with(&root).then_some((root, base))
+}
+
+/// The names declared in the packaging files of the folder, the symbolic links are skipped
+fn declared_names_in(root: &Path, folder: &Path) -> Vec<DeclaredName> {
+    let mut names = vec![];
+    for (file, parse) in [
+        (
+            "pyproject.toml",
+            pyproject_name as fn(&str) -> Option<String>,
+        ),
+        ("setup.cfg", setup_cfg_name),
+        ("setup.py", setup_py_name),
+    ] {
+        let path = folder.join(file);
+        if !fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.is_file()) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Some(name) = parse(&content) {
+            names.push(DeclaredName {
+                file: path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .to_string(),
+                name,
+            });
+        }
+    }
+    names
+}
+
+pub fn pyproject_name(content: &str) -> Option<String> {
+    ini_style_name(content, &PYPROJECT_SECTIONS, true)
+}
+
+pub fn setup_cfg_name(content: &str) -> Option<String> {
+    ini_style_name(content, &SETUP_CFG_SECTIONS, false)
+}
+
+/// The name given as a literal in the call of setup()
+pub fn setup_py_name(content: &str) -> Option<String> {
+    SETUP_PY_NAME
+        .captures(content)
+        .map(|caps| caps[1].to_string())
+}
+
+/// The `name = value` line in one of the sections. In TOML the value has to be a string.
+fn ini_style_name(content: &str, sections: &[&str], quoted: bool) -> Option<String> {
+    let mut in_section = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_section = sections.contains(&line);
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if key.trim() != "name" {
+            continue;
+        }
+        let value = value.trim();
+        let name = if quoted {
+            let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            value[1..].split(quote).next()?
+        } else {
+            value
+        };
+        if !name.is_empty() {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pyproject_name() {
+        assert_eq!(
+            pyproject_name("[build-system]\nrequires = []\n\n[project]\nname = \"Flask\"\n"),
+            Some(String::from("Flask"))
+        );
+        assert_eq!(
+            pyproject_name("[tool.poetry]\nname = 'my-tool'  # the name\n"),
+            Some(String::from("my-tool"))
+        );
+        assert_eq!(
+            pyproject_name("[tool.black]\nname = \"other\"\n[project]\ndynamic = [\"name\"]\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_setup_cfg_name() {
+        assert_eq!(
+            setup_cfg_name("[metadata]\nname = my_tool\nversion = 1.0\n"),
+            Some(String::from("my_tool"))
+        );
+        assert_eq!(setup_cfg_name("[options]\nname = my_tool\n"), None);
+    }
+
+    #[test]
+    fn test_setup_py_name() {
+        assert_eq!(
+            setup_py_name(
+                "from setuptools import setup\n\nsetup(\n    name=\"Jinja2\",\n    version=\"3.0\",\n)\n"
+            ),
+            Some(String::from("Jinja2"))
+        );
+        assert_eq!(setup_py_name("setup(name=NAME)\n"), None);
+    }
+
+    #[test]
+    fn test_tag_lag_bucket() {
+        assert_eq!(tag_lag_bucket(-3), "tagged-after-upload");
+        assert_eq!(tag_lag_bucket(0), "within-a-day");
+        assert_eq!(tag_lag_bucket(30), "within-a-week");
+        assert_eq!(tag_lag_bucket(24 * 10), "within-a-month");
+        assert_eq!(tag_lag_bucket(24 * 400), "over-a-month");
+    }
+
+    #[test]
+    fn test_check() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let path = temp_folder.path();
+        let provenance = Provenance::check("flask", path, None);
+        assert_eq!(provenance.name_matches, None);
+
+        fs::write(
+            path.join("pyproject.toml"),
+            "[project]\nname = \"workspace\"\n",
+        )
+        .unwrap();
+        assert!(Provenance::check("flask", path, None).is_name_mismatch());
+
+        fs::create_dir_all(path.join("flask")).unwrap();
+        fs::write(path.join("flask/setup.cfg"), "[metadata]\nname = Flask\n").unwrap();
+        let provenance = Provenance::check("flask", path, None);
+        assert_eq!(provenance.name_matches, Some(true));
+        assert_eq!(
+            provenance.declared_names[1],
+            DeclaredName {
+                file: String::from("flask/setup.cfg"),
+                name: String::from("Flask"),
+            }
+        );
+
+        let provenance = Provenance::check("flask", path, Some("flask"));
+        assert_eq!(provenance.declared_names.len(), 1);
+        assert_eq!(provenance.name_matches, Some(true));
+    }
+
+    #[test]
+    fn test_check_stays_in_the_repository() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let outside = temp_folder.path().join("home");
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("setup.cfg"), "[metadata]\nname = secret\n").unwrap();
+        let path = temp_folder.path().join("clone");
+        fs::create_dir_all(&path).unwrap();
+        std::os::unix::fs::symlink(&outside, path.join("pkg")).unwrap();
+
+        for subdirectory in ["../home", "pkg", "missing"] {
+            let provenance = Provenance::check("flask", &path, Some(subdirectory));
+            assert!(provenance.declared_names.is_empty(), "{subdirectory}");
+            assert_eq!(provenance.name_matches, None);
+        }
+        // The link is not followed from the root either
+        assert!(
+            Provenance::check("flask", &path, None)
+                .declared_names
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_check_skips_symbolic_links() {
+        let temp_folder = tempfile::tempdir().unwrap();
+        let secret = temp_folder.path().join("secret.toml");
+        fs::write(&secret, "[project]\nname = \"secret\"\n").unwrap();
+        let path = temp_folder.path().join("clone");
+        fs::create_dir_all(path.join("sub")).unwrap();
+        std::os::unix::fs::symlink(&secret, path.join("pyproject.toml")).unwrap();
+        std::os::unix::fs::symlink(&secret, path.join("sub/pyproject.toml")).unwrap();
+        fs::write(path.join("setup.py"), "setup(name=\"flask\")\n").unwrap();
+
+        let provenance = Provenance::check("flask", &path, None);
+        assert_eq!(
+            provenance.declared_names,
+            vec![DeclaredName {
+                file: String::from("setup.py"),
+                name: String::from("flask"),
+            }]
+        );
+        assert_eq!(provenance.name_matches, Some(true));
+    }
+}
@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::MyFilteredProject;
+
+/// The Python releases we check requires_python against,
+/// with the date of the release and the date of the end of life.
+/// See https://devguide.python.org/versions/, the future ends of life are the planned ones.
+const PYTHON_RELEASES: [((u32, u32), NaiveDate, NaiveDate); 18] = [
+    ((2, 6), date(2008, 10, 1), date(2013, 10, 29)),
+    ((2, 7), date(2010, 7, 3), date(2020, 1, 1)),
+    ((3, 0), date(2008, 12, 3), date(2009, 6, 27)),
+    ((3, 1), date(2009, 6, 27), date(2012, 4, 9)),
+    ((3, 2), date(2011, 2, 20), date(2016, 2, 20)),
+    ((3, 3), date(2012, 9, 29), date(2017, 9, 29)),
+    ((3, 4), date(2014, 3, 16), date(2019, 3, 18)),
+    ((3, 5), date(2015, 9, 13), date(2020, 9, 30)),
+    ((3, 6), date(2016, 12, 23), date(2021, 12, 23)),
+    ((3, 7), date(2018, 6, 27), date(2023, 6, 27)),
+    ((3, 8), date(2019, 10, 14), date(2024, 10, 7)),
+    ((3, 9), date(2020, 10, 5), date(2025, 10, 31)),
+    ((3, 10), date(2021, 10, 4), date(2026, 10, 4)),
+    ((3, 11), date(2022, 10, 24), date(2027, 10, 24)),
+    ((3, 12), date(2023, 10, 2), date(2028, 10, 2)),
+    ((3, 13), date(2024, 10, 7), date(2029, 10, 7)),
+    ((3, 14), date(2025, 10, 7), date(2030, 10, 7)),
+    ((3, 15), date(2026, 10, 1), date(2031, 10, 1)),
+];
+
+const fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+const CLASSIFIER_PREFIX: &str = "Programming Language :: Python :: ";
+
+#[derive(Debug, Serialize)]
+pub struct PythonReport {
+    /// Number of projects allowing each Python version according to requires_python
+    pub requires_python_versions: HashMap<String, u32>,
+    /// Number of projects listing each Python version in the classifiers
+    pub classifier_versions: HashMap<String, u32>,
+    pub no_requires_python_count: u32,
+    pub no_requires_python: Vec<MyFilteredProject>,
+    pub bad_requires_python_count: u32,
+    pub bad_requires_python: Vec<MyFilteredProject>,
+    pub allows_eol_python_count: u32,
+    pub allows_eol_python: Vec<MyFilteredProject>,
+    pub mismatch_count: u32,
+    pub mismatch: Vec<MyFilteredProject>,
+}
+
+pub fn format_version(version: (u32, u32)) -> String {
+    format!("{}.{}", version.0, version.1)
+}
+
+/// The known Python versions, oldest first
+pub fn known_versions() -> impl Iterator<Item = (u32, u32)> {
+    PYTHON_RELEASES.iter().map(|(version, _, _)| *version)
+}
+
+/// The version reached its end of life by `now`.
+/// Unknown versions are end of life if they are older than all the known ones.
+pub fn is_eol(version: (u32, u32), now: DateTime<Utc>) -> bool {
+    match PYTHON_RELEASES
+        .iter()
+        .find(|(known, _, _)| *known == version)
+    {
+        Some((_, _, end_of_life)) => *end_of_life <= now.date_naive(),
+        None => known_versions()
+            .next()
+            .is_some_and(|oldest| version < oldest),
+    }
+}
+
+/// The known Python versions allowed by a requires_python specifier such as `>=3.9`.
+/// Returns None if the specifier cannot be parsed.
+pub fn versions_from_requires_python(requires_python: &str) -> Option<Vec<(u32, u32)>> {
+    let mut clauses = vec![];
+    for clause in requires_python
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+    {
+        clauses.push(Clause::parse(clause)?);
+    }
+
+    Some(
+        known_versions()
+            .filter(|version| clauses.iter().all(|clause| clause.allows(*version)))
+            .collect(),
+    )
+}
+
+/// The Python versions listed as `Programming Language :: Python :: 3.12` classifiers.
+/// The major-only classifiers (`Python :: 3`, `Python :: 3 :: Only`) are ignored.
+pub fn versions_from_classifiers(classifiers: &[String]) -> Vec<(u32, u32)> {
+    let mut versions: Vec<(u32, u32)> = classifiers
+        .iter()
+        .filter_map(|classifier| classifier.strip_prefix(CLASSIFIER_PREFIX))
+        .filter_map(|version| {
+            let (major, minor) = version.trim().split_once('.')?;
+            Some((major.parse().ok()?, minor.parse().ok()?))
+        })
+        .collect();
+    versions.sort();
+    versions.dedup();
+    versions
+}
+
+/// The Python versions the project supports, formatted as `3.12`.
+/// requires_python is used when it can be parsed, otherwise the versions listed in the classifiers.
+pub fn supported_versions(requires_python: Option<&str>, classifiers: &[String]) -> Vec<String> {
+    let versions = requires_python
+        .filter(|requires_python| !requires_python.trim().is_empty())
+        .and_then(versions_from_requires_python)
+        .unwrap_or_else(|| versions_from_classifiers(classifiers));
+    versions.into_iter().map(format_version).collect()
+}
+
+/// The classifiers disagree with requires_python if they list a version that requires_python
+/// excludes, or if requires_python allows older versions than the oldest classifier.
+/// Newer versions missing from the classifiers are not a mismatch, those are often added later.
+pub fn is_mismatch(allowed: &[(u32, u32)], classifiers: &[(u32, u32)]) -> bool {
+    if classifiers.is_empty() {
+        return false;
+    }
+    if classifiers.iter().any(|version| {
+        known_versions().any(|known| known == *version) && !allowed.contains(version)
+    }) {
+        return true;
+    }
+    match (allowed.first(), classifiers.first()) {
+        (Some(oldest_allowed), Some(oldest_classifier)) => oldest_allowed < oldest_classifier,
+        _ => false,
+    }
+}
+
+/// A single comparison from a requires_python specifier, e.g. `>=3.9` or `!=3.0.*`
+struct Clause {
+    operator: String,
+    release: Vec<u32>,
+    wildcard: bool,
+}
+
+impl Clause {
+    fn parse(clause: &str) -> Option<Clause> {
+        let operator: String = clause
+            .chars()
+            .take_while(|c| matches!(c, '<' | '>' | '=' | '!' | '~'))
+            .collect();
+        if !matches!(
+            operator.as_str(),
+            ">=" | ">" | "<=" | "<" | "==" | "===" | "!=" | "~="
+        ) {
+            return None;
+        }
+
+        let version = clause[operator.len()..].trim();
+        let (version, wildcard) = match version.strip_suffix(".*") {
+            Some(version) => (version, true),
+            None => (version, false),
+        };
+        let release = version
+            .split('.')
+            .map(|part| part.parse::<u32>().ok())
+            .collect::<Option<Vec<u32>>>()?;
+
+        Some(Clause {
+            operator,
+            release,
+            wildcard,
+        })
+    }
+
+    fn part(&self, index: usize) -> u32 {
+        self.release.get(index).copied().unwrap_or(0)
+    }
+
+    /// Does any release in the X.Y series satisfy this clause?
+    fn allows(&self, version: (u32, u32)) -> bool {
+        let bound = (self.part(0), self.part(1));
+        let has_patch = self.release.iter().skip(2).any(|part| *part > 0);
+        match self.operator.as_str() {
+            ">=" | ">" => version >= bound,
+            "<=" => version <= bound,
+            "<" => version < bound || (version == bound && has_patch),
+            "==" | "===" => {
+                if self.wildcard && self.release.len() == 1 {
+                    version.0 == bound.0
+                } else {
+                    version == bound
+                }
+            }
+            "!=" => {
+                if !self.wildcard {
+                    return true;
+                }
+                match self.release.len() {
+                    1 => version.0 != bound.0,
+                    2 => version != bound,
+                    _ => true,
+                }
+            }
+            "~=" => {
+                if self.release.len() <= 2 {
+                    version >= bound && version.0 == bound.0
+                } else {
+                    version == bound
+                }
+            }
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_from_requires_python() {
+        assert_eq!(
+            versions_from_requires_python(">=3.12"),
+            Some(vec![(3, 12), (3, 13), (3, 14), (3, 15)])
+        );
+        assert_eq!(
+            versions_from_requires_python(">=3.8, <3.11"),
+            Some(vec![(3, 8), (3, 9), (3, 10)])
+        );
+        assert_eq!(
+            versions_from_requires_python("<3.10.2,>=3.9"),
+            Some(vec![(3, 9), (3, 10)])
+        );
+        assert_eq!(
+            versions_from_requires_python(">=2.7, !=3.0.*, !=3.1.*, !=3.2.*, !=3.3.*, <3.6"),
+            Some(vec![(2, 7), (3, 4), (3, 5)])
+        );
+        assert_eq!(
+            versions_from_requires_python("~=3.12"),
+            Some(vec![(3, 12), (3, 13), (3, 14), (3, 15)])
+        );
+        assert_eq!(
+            versions_from_requires_python("==3.11.*"),
+            Some(vec![(3, 11)])
+        );
+        assert_eq!(versions_from_requires_python("3.8"), None);
+        assert_eq!(versions_from_requires_python(">=3.x"), None);
+    }
+
+    #[test]
+    fn test_versions_from_classifiers() {
+        let classifiers = vec![
+            String::from("Programming Language :: Python"),
+            String::from("Programming Language :: Python :: 3"),
+            String::from("Programming Language :: Python :: 3 :: Only"),
+            String::from("Programming Language :: Python :: 3.13"),
+            String::from("Programming Language :: Python :: 3.12"),
+            String::from("Programming Language :: Python :: Implementation :: CPython"),
+        ];
+        assert_eq!(
+            versions_from_classifiers(&classifiers),
+            vec![(3, 12), (3, 13)]
+        );
+    }
+
+    #[test]
+    fn test_is_mismatch() {
+        let allowed = versions_from_requires_python(">=3.6").unwrap();
+        assert!(is_mismatch(&allowed, &[(3, 12)]));
+
+        let allowed = versions_from_requires_python(">=3.10").unwrap();
+        assert!(!is_mismatch(&allowed, &[(3, 10), (3, 11)]));
+        assert!(is_mismatch(&allowed, &[(3, 9), (3, 10)]));
+        assert!(!is_mismatch(&allowed, &[]));
+    }
+
+    #[test]
+    fn test_is_eol() {
+        let now = DateTime::parse_from_rfc3339("2026-10-18T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(is_eol((2, 5), now));
+        assert!(is_eol((2, 7), now));
+        assert!(is_eol((3, 9), now));
+        assert!(is_eol((3, 10), now));
+        assert!(!is_eol((3, 11), now));
+        assert!(!is_eol((3, 15), now));
+        assert!(!is_eol((4, 0), now));
+
+        let before = DateTime::parse_from_rfc3339("2026-10-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(!is_eol((3, 10), before));
+    }
+
+    #[test]
+    fn test_supported_versions() {
+        let classifiers = vec![
+            String::from("Programming Language :: Python :: 3.11"),
+            String::from("Programming Language :: Python :: 3.12"),
+        ];
+        assert_eq!(
+            supported_versions(Some(">=3.13"), &classifiers),
+            vec!["3.13", "3.14", "3.15"]
+        );
+        assert_eq!(supported_versions(None, &classifiers), vec!["3.11", "3.12"]);
+        assert_eq!(
+            supported_versions(Some("~= py3"), &classifiers),
+            vec!["3.11", "3.12"]
+        );
+        assert!(supported_versions(Some(" "), &[]).is_empty());
+    }
+}
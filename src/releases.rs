@@ -0,0 +1,281 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::serde::{ts_seconds, ts_seconds_option};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::versions::Version;
+use crate::{MyFilteredProject, UrlInfo};
+
+/// A previous release after this many days of silence means the project came back from dormancy
+pub const DORMANT_DAYS: i64 = 365;
+
+/// More releases than this within RECENT_DAYS of the latest one is suspiciously often
+pub const FREQUENT_RELEASES: usize = 10;
+pub const RECENT_DAYS: i64 = 7;
+
+/// One version from the releases map of the PyPI JSON
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Release {
+    pub version: String,
+    /// The time the first file of the release was uploaded, None if it has no files
+    pub upload_time: Option<DateTime<Utc>>,
+    /// All the files of the release were yanked
+    pub yanked: bool,
+    pub yanked_reason: Option<String>,
+}
+
+/// Statistics about the history of a project, computed from the releases
+/// up to and including the current version
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct ReleaseStats {
+    #[serde(with = "ts_seconds")]
+    pub first_release: DateTime<Utc>,
+    pub release_count: usize,
+    pub median_seconds_between_releases: Option<i64>,
+    /// Days between the current release and the one before it
+    pub days_since_previous_release: Option<i64>,
+    /// Number of releases within RECENT_DAYS before the current one, including it
+    pub recent_release_count: usize,
+    pub yanked_versions: Vec<String>,
+    /// The reasons given for the yanked versions that have one
+    #[serde(default)]
+    pub yanked_reasons: BTreeMap<String, String>,
+    /// The upload time of the yanked versions that have files
+    #[serde(default)]
+    pub yanked_upload_times: BTreeMap<String, DateTime<Utc>>,
+    pub pre_release_count: usize,
+    #[serde(default, with = "ts_seconds_option")]
+    pub previous_release: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReleaseReport {
+    pub new_projects_count: u32,
+    pub new_projects: Vec<MyFilteredProject>,
+    pub dormant_returned_count: u32,
+    pub dormant_returned: Vec<MyFilteredProject>,
+    pub frequent_releases_count: u32,
+    pub frequent_releases: Vec<MyFilteredProject>,
+    pub has_yanked_versions_count: u32,
+    pub has_yanked_versions: Vec<MyFilteredProject>,
+}
+
+pub fn releases_from_map(releases: &HashMap<String, Vec<UrlInfo>>) -> Vec<Release> {
+    let mut releases: Vec<Release> = releases
+        .iter()
+        .map(|(version, files)| Release {
+            version: version.clone(),
+            upload_time: files
+                .iter()
+                .filter_map(|file| file.upload_time_iso_8601)
+                .min(),
+            yanked: !files.is_empty() && files.iter().all(|file| file.yanked == Some(true)),
+            yanked_reason: files
+                .iter()
+                .filter_map(|file| file.yanked_reason.clone())
+                .find(|reason| !reason.is_empty()),
+        })
+        .collect();
+    // Releases without files sort first, we don't know when they were made
+    releases.sort_by(|a, b| {
+        a.upload_time
+            .cmp(&b.upload_time)
+            .then_with(|| a.version.cmp(&b.version))
+    });
+    releases
+}
+
+/// Pre-releases such as 1.0a1, 2.0b3, 3.0rc1 or 1.0.dev2, invalid versions are not counted
+pub fn is_pre_release(version: &str) -> bool {
+    Version::parse(version).is_some_and(|version| version.is_pre_release())
+}
+
+impl ReleaseStats {
+    /// None if there is no release with an upload time
+    pub fn from_releases(releases: &[Release], current_version: &str) -> Option<ReleaseStats> {
+        // Ignore the releases made after the current one
+        let current_time = releases
+            .iter()
+            .find(|release| release.version == current_version)
+            .and_then(|release| release.upload_time);
+        let releases: Vec<&Release> = releases
+            .iter()
+            .filter(|release| match (current_time, release.upload_time) {
+                (Some(current), Some(time)) => time <= current,
+                _ => true,
+            })
+            .collect();
+
+        let times: Vec<DateTime<Utc>> = releases
+            .iter()
+            .filter_map(|release| release.upload_time)
+            .collect();
+        let first_release = *times.first()?;
+        let latest = *times.last()?;
+
+        let mut gaps: Vec<i64> = times
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).num_seconds())
+            .collect();
+        gaps.sort();
+        let median_seconds_between_releases = match gaps.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(gaps[n / 2]),
+            n => Some((gaps[n / 2 - 1] + gaps[n / 2]) / 2),
+        };
+
+        let previous_release = times.iter().rev().nth(1).copied();
+        let recent_release_count = times
+            .iter()
+            .filter(|time| latest - **time <= Duration::days(RECENT_DAYS))
+            .count();
+
+        let pre_release_count = releases
+            .iter()
+            .filter(|release| is_pre_release(&release.version))
+            .count();
+
+        Some(ReleaseStats {
+            first_release,
+            release_count: releases.len(),
+            median_seconds_between_releases,
+            days_since_previous_release: previous_release
+                .map(|previous| (latest - previous).num_days()),
+            recent_release_count,
+            yanked_versions: releases
+                .iter()
+                .filter(|release| release.yanked)
+                .map(|release| release.version.clone())
+                .collect(),
+            yanked_reasons: releases
+                .iter()
+                .filter(|release| release.yanked)
+                .filter_map(|release| {
+                    release
+                        .yanked_reason
+                        .clone()
+                        .map(|reason| (release.version.clone(), reason))
+                })
+                .collect(),
+            yanked_upload_times: releases
+                .iter()
+                .filter(|release| release.yanked)
+                .filter_map(|release| {
+                    release
+                        .upload_time
+                        .map(|time| (release.version.clone(), time))
+                })
+                .collect(),
+            pre_release_count,
+            previous_release,
+        })
+    }
+
+    /// The share of the releases that are pre-releases, 0 if there are no releases
+    pub fn pre_release_ratio(&self) -> f64 {
+        if self.release_count == 0 {
+            return 0.0;
+        }
+        self.pre_release_count as f64 / self.release_count as f64
+    }
+
+    pub fn is_new_project(&self) -> bool {
+        self.release_count == 1
+    }
+
+    pub fn is_dormant_returned(&self) -> bool {
+        self.days_since_previous_release
+            .is_some_and(|days| days > DORMANT_DAYS)
+    }
+
+    pub fn is_frequent(&self) -> bool {
+        self.recent_release_count > FREQUENT_RELEASES
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(version: &str, time: Option<&str>, yanked: bool) -> Release {
+        Release {
+            yanked_reason: yanked.then(|| String::from("broken build")),
+            version: String::from(version),
+            upload_time: time.map(|time| {
+                DateTime::parse_from_rfc3339(time)
+                    .unwrap()
+                    .with_timezone(&Utc)
+            }),
+            yanked,
+        }
+    }
+
+    #[test]
+    fn test_is_pre_release() {
+        assert!(is_pre_release("1.0a1"));
+        assert!(is_pre_release("2.0b3"));
+        assert!(is_pre_release("3.0rc1"));
+        assert!(is_pre_release("1.0.dev2"));
+        assert!(is_pre_release("0.5beta"));
+        assert!(is_pre_release("1.0-alpha.1"));
+        assert!(!is_pre_release("1.0"));
+        assert!(!is_pre_release("1.0.post1"));
+        assert!(!is_pre_release("1.0+abc"));
+    }
+
+    #[test]
+    fn test_from_releases() {
+        let releases = vec![
+            release("0.1", None, false),
+            release("1.0", Some("2020-01-01T00:00:00Z"), false),
+            release("1.1rc1", Some("2020-01-11T00:00:00Z"), true),
+            release("1.1", Some("2020-01-31T00:00:00Z"), false),
+            release("2.0", Some("2022-01-31T00:00:00Z"), false),
+            release("3.0", Some("2023-01-31T00:00:00Z"), false),
+        ];
+        let stats = ReleaseStats::from_releases(&releases, "2.0").unwrap();
+        assert_eq!(stats.release_count, 5);
+        assert_eq!(stats.first_release, releases[1].upload_time.unwrap());
+        // gaps are 10, 20 and 731 days
+        assert_eq!(stats.median_seconds_between_releases, Some(20 * 86400));
+        assert_eq!(stats.days_since_previous_release, Some(731));
+        assert_eq!(stats.previous_release, releases[3].upload_time);
+        assert_eq!(stats.recent_release_count, 1);
+        assert_eq!(stats.yanked_versions, vec!["1.1rc1"]);
+        assert_eq!(
+            stats.yanked_reasons,
+            BTreeMap::from([(String::from("1.1rc1"), String::from("broken build"))])
+        );
+        assert_eq!(
+            stats.yanked_upload_times,
+            BTreeMap::from([(String::from("1.1rc1"), releases[2].upload_time.unwrap())])
+        );
+        assert_eq!(stats.pre_release_count, 1);
+        assert_eq!(stats.pre_release_ratio(), 0.2);
+        assert!(stats.is_dormant_returned());
+        assert!(!stats.is_new_project());
+        assert!(!stats.is_frequent());
+    }
+
+    #[test]
+    fn test_from_releases_single() {
+        let releases = vec![release("0.0.1", Some("2026-01-16T07:46:02Z"), false)];
+        let stats = ReleaseStats::from_releases(&releases, "0.0.1").unwrap();
+        assert!(stats.is_new_project());
+        assert_eq!(stats.median_seconds_between_releases, None);
+        assert_eq!(stats.days_since_previous_release, None);
+        assert_eq!(stats.pre_release_ratio(), 0.0);
+
+        let empty = ReleaseStats {
+            release_count: 0,
+            ..stats
+        };
+        assert_eq!(empty.pre_release_ratio(), 0.0);
+
+        assert_eq!(
+            ReleaseStats::from_releases(&[release("0.1", None, false)], "0.1"),
+            None
+        );
+    }
+}
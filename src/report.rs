@@ -1,12 +1,25 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
+use chrono::{DateTime, Duration, Utc};
 use tracing::{error, info};
 
-use git_digger::Repository;
-
-use pydigger::{LicenseReport, MyProject, PAGE_SIZE, Report, VCSReport};
+use pydigger::repository::VcsHosts;
+use pydigger::{
+    DependencyCount, DependencyReport, LicenseReport, MyFilteredProject, MyProject, PAGE_SIZE,
+    Report, VCSReport, activity::ActivityReport, classifiers, classifiers::ClassifierReport,
+    description, description::DescriptionReport, distributions, distributions::DistributionKind,
+    distributions::DistributionReport, normalize_package_name, people::PeopleReport,
+    people::UploaderCount, provenance, provenance::MismatchedProject, provenance::ProvenanceReport,
+    python_versions, python_versions::PythonReport, releases::ReleaseReport, repository,
+    repository::ConflictingProject, repository::RepositoryReport, requirements::RequirementsReport,
+    risk, risk::RiskReport, risk::RiskyProject, typosquatting::SuspiciousName,
+    typosquatting::TyposquattingReport, urls::DomainReport, urls::HostCount, versions,
+    versions::Version, versions::VersionReport, vulnerabilities,
+    vulnerabilities::VulnerabilityReport, vulnerabilities::VulnerableProject,
+    vulnerabilities::YankedRelease, wheels::WheelReport,
+};
 
 pub fn get_pypi_path() -> String {
     String::from("data/pypi")
@@ -15,7 +28,7 @@ pub fn get_pypi_path() -> String {
 /// Generate a report by counting all project JSON files in get_pypi_path()
 /// Returns the total count of projects and writes the report to report.json
 /// TODO: Which project has repository URL, license and which does not
-pub fn generate_report() -> Result<(), Box<dyn std::error::Error>> {
+pub fn generate_report(vcs_hosts: &VcsHosts) -> Result<(), Box<dyn std::error::Error>> {
     let pypi_dir = get_pypi_path();
     let pypi_dir = Path::new(&pypi_dir);
 
@@ -24,8 +37,27 @@ pub fn generate_report() -> Result<(), Box<dyn std::error::Error>> {
 
     let pages_size = total_projects.min(PAGE_SIZE);
     let lr = create_license_report(&all_projects);
-    let vcs = create_vcs_report(&all_projects);
+    let vcs = create_vcs_report(&all_projects, vcs_hosts);
     let project_urls_count = create_urls_report(&all_projects);
+    let dependencies = create_dependency_report(&all_projects);
+    let reverse_dependencies = create_reverse_dependencies(&all_projects);
+    let requirements = create_requirements_report(&all_projects);
+    let python = create_python_report(&all_projects, Utc::now());
+    let classifiers = create_classifier_report(&all_projects);
+    let distributions = create_distribution_report(&all_projects);
+    let wheels = create_wheel_report(&all_projects);
+    let releases = create_release_report(&all_projects);
+    let versions = create_version_report(&all_projects);
+    let vulnerabilities = create_vulnerability_report(&all_projects, Utc::now());
+    let people = create_people_report(&all_projects);
+    let typosquatting = create_typosquatting_report(&all_projects);
+    let risk = create_risk_report(&all_projects);
+    let description = create_description_report(&all_projects);
+    let domains = create_domain_report(&all_projects);
+    let repository = create_repository_report(&all_projects);
+    let provenance = create_provenance_report(&all_projects);
+    let activity = create_activity_report(&all_projects);
+    let maintainer_index = create_maintainer_index(&all_projects);
 
     // Create the report
     let report = Report {
@@ -37,7 +69,24 @@ pub fn generate_report() -> Result<(), Box<dyn std::error::Error>> {
             .collect(),
         license: lr,
         vcs,
-        project_urls_count: project_urls_count,
+        dependencies,
+        requirements,
+        python,
+        classifiers,
+        distributions,
+        wheels,
+        releases,
+        versions,
+        vulnerabilities,
+        people,
+        typosquatting,
+        risk,
+        description,
+        domains,
+        repository,
+        provenance,
+        activity,
+        project_urls_count,
     };
     let report_json = serde_json::to_string_pretty(&report)?;
 
@@ -48,9 +97,993 @@ pub fn generate_report() -> Result<(), Box<dyn std::error::Error>> {
         total_projects
     );
 
+    let reverse_dependencies_json = serde_json::to_string_pretty(&reverse_dependencies)?;
+    fs::write("data/reverse_dependencies.json", reverse_dependencies_json)?;
+    info!(
+        "Generated data/reverse_dependencies.json with {} depended-upon projects",
+        reverse_dependencies.len()
+    );
+
+    let maintainer_index_json = serde_json::to_string_pretty(&maintainer_index)?;
+    fs::write("data/maintainers.json", maintainer_index_json)?;
+    info!(
+        "Generated data/maintainers.json with {} people",
+        maintainer_index.len()
+    );
+
     Ok(())
 }
 
+/// Count how many of the collected projects depend on each package
+fn create_dependency_report(projects: &[MyProject]) -> DependencyReport {
+    let collected: HashSet<String> = projects
+        .iter()
+        .map(|project| normalize_package_name(&project.name))
+        .collect();
+
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    let mut dr = DependencyReport {
+        distinct_dependencies: 0,
+        most_depended_upon: vec![],
+        no_dependencies_count: 0,
+        no_dependencies: vec![],
+    };
+
+    for project in projects.iter() {
+        let dependencies = project.dependency_names();
+        if dependencies.is_empty() {
+            dr.no_dependencies_count += 1;
+            if dr.no_dependencies.len() < PAGE_SIZE {
+                dr.no_dependencies.push(project.smaller());
+            }
+            continue;
+        }
+        for name in dependencies {
+            *counts.entry(name).or_insert(0) += 1;
+        }
+    }
+
+    let mut ranked: Vec<(String, u32)> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    dr.distinct_dependencies = ranked.len();
+    dr.most_depended_upon = ranked
+        .into_iter()
+        .take(PAGE_SIZE)
+        .map(|(name, count)| DependencyCount {
+            collected: collected.contains(&name),
+            name,
+            count,
+        })
+        .collect();
+
+    dr
+}
+
+/// Classify the version specifiers of every requirement.
+/// A project is listed in a section if at least one of its requirements falls in it.
+fn create_requirements_report(projects: &[MyProject]) -> RequirementsReport {
+    let mut rr = RequirementsReport {
+        kinds: HashMap::new(),
+        unpinned_count: 0,
+        unpinned: vec![],
+        upper_bounded_only_count: 0,
+        upper_bounded_only: vec![],
+        exact_pin_count: 0,
+        exact_pin: vec![],
+        major_cap_count: 0,
+        major_cap: vec![],
+        url_count: 0,
+        url: vec![],
+        local_path_count: 0,
+        local_path: vec![],
+    };
+
+    for project in projects.iter() {
+        let requirements = project.requirements();
+        for req in requirements.iter() {
+            *rr.kinds.entry(String::from("total")).or_insert(0) += 1;
+            let kinds = [
+                ("unpinned", req.is_unpinned()),
+                ("upper_bounded_only", req.is_upper_bounded_only()),
+                ("exact_pin", req.is_exact_pin()),
+                ("major_cap", req.has_major_cap()),
+                ("url", req.is_url()),
+                ("local_path", req.is_local_path()),
+            ];
+            for (kind, matches) in kinds {
+                if matches {
+                    *rr.kinds.entry(String::from(kind)).or_insert(0) += 1;
+                }
+            }
+        }
+
+        if requirements.iter().any(|req| req.is_unpinned()) {
+            rr.unpinned_count += 1;
+            if rr.unpinned.len() < PAGE_SIZE {
+                rr.unpinned.push(project.smaller());
+            }
+        }
+        if requirements.iter().any(|req| req.is_upper_bounded_only()) {
+            rr.upper_bounded_only_count += 1;
+            if rr.upper_bounded_only.len() < PAGE_SIZE {
+                rr.upper_bounded_only.push(project.smaller());
+            }
+        }
+        if requirements.iter().any(|req| req.is_exact_pin()) {
+            rr.exact_pin_count += 1;
+            if rr.exact_pin.len() < PAGE_SIZE {
+                rr.exact_pin.push(project.smaller());
+            }
+        }
+        if requirements.iter().any(|req| req.has_major_cap()) {
+            rr.major_cap_count += 1;
+            if rr.major_cap.len() < PAGE_SIZE {
+                rr.major_cap.push(project.smaller());
+            }
+        }
+        if requirements.iter().any(|req| req.is_url()) {
+            info!("Project {} has a URL in its requirements", project.name);
+            rr.url_count += 1;
+            if rr.url.len() < PAGE_SIZE {
+                rr.url.push(project.smaller());
+            }
+        }
+        if requirements.iter().any(|req| req.is_local_path()) {
+            info!(
+                "Project {} has a local path in its requirements",
+                project.name
+            );
+            rr.local_path_count += 1;
+            if rr.local_path.len() < PAGE_SIZE {
+                rr.local_path.push(project.smaller());
+            }
+        }
+    }
+
+    rr
+}
+
+/// Which Python versions the projects support according to requires_python and the classifiers
+fn create_python_report(projects: &[MyProject], now: DateTime<Utc>) -> PythonReport {
+    let mut pr = PythonReport {
+        requires_python_versions: HashMap::new(),
+        classifier_versions: HashMap::new(),
+        no_requires_python_count: 0,
+        no_requires_python: vec![],
+        bad_requires_python_count: 0,
+        bad_requires_python: vec![],
+        allows_eol_python_count: 0,
+        allows_eol_python: vec![],
+        mismatch_count: 0,
+        mismatch: vec![],
+    };
+
+    for project in projects.iter() {
+        let classifier_versions = python_versions::versions_from_classifiers(&project.classifiers);
+        for version in classifier_versions.iter() {
+            *pr.classifier_versions
+                .entry(python_versions::format_version(*version))
+                .or_insert(0) += 1;
+        }
+
+        let requires_python = match &project.requires_python {
+            Some(requires_python) if !requires_python.trim().is_empty() => requires_python,
+            _ => {
+                pr.no_requires_python_count += 1;
+                if pr.no_requires_python.len() < PAGE_SIZE {
+                    pr.no_requires_python.push(project.smaller());
+                }
+                continue;
+            }
+        };
+
+        let Some(allowed) = python_versions::versions_from_requires_python(requires_python) else {
+            info!(
+                "Unrecognized requires_python '{}' in project {}",
+                requires_python, project.name
+            );
+            pr.bad_requires_python_count += 1;
+            if pr.bad_requires_python.len() < PAGE_SIZE {
+                pr.bad_requires_python.push(project.smaller());
+            }
+            continue;
+        };
+
+        for version in allowed.iter() {
+            *pr.requires_python_versions
+                .entry(python_versions::format_version(*version))
+                .or_insert(0) += 1;
+        }
+
+        if allowed
+            .iter()
+            .any(|version| python_versions::is_eol(*version, now))
+        {
+            pr.allows_eol_python_count += 1;
+            if pr.allows_eol_python.len() < PAGE_SIZE {
+                pr.allows_eol_python.push(project.smaller());
+            }
+        }
+
+        if python_versions::is_mismatch(&allowed, &classifier_versions) {
+            info!(
+                "requires_python '{}' does not match the classifiers in project {}",
+                requires_python, project.name
+            );
+            pr.mismatch_count += 1;
+            if pr.mismatch.len() < PAGE_SIZE {
+                pr.mismatch.push(project.smaller());
+            }
+        }
+    }
+
+    pr
+}
+
+/// Validate the classifiers and count them by category
+fn create_classifier_report(projects: &[MyProject]) -> ClassifierReport {
+    let mut cr = ClassifierReport {
+        categories: classifiers::REPORTED_CATEGORIES
+            .iter()
+            .map(|category| (category.to_string(), HashMap::new()))
+            .collect(),
+        no_classifiers_count: 0,
+        no_classifiers: vec![],
+        unknown: HashMap::new(),
+        unknown_classifiers_count: 0,
+        unknown_classifiers: vec![],
+        deprecated: HashMap::new(),
+        deprecated_classifiers_count: 0,
+        deprecated_classifiers: vec![],
+        typed_count: 0,
+    };
+
+    for project in projects.iter() {
+        if project.classifiers.is_empty() {
+            cr.no_classifiers_count += 1;
+            if cr.no_classifiers.len() < PAGE_SIZE {
+                cr.no_classifiers.push(project.smaller());
+            }
+            continue;
+        }
+
+        let mut has_unknown = false;
+        let mut has_deprecated = false;
+        for classifier in project.classifiers.iter() {
+            if classifiers::is_deprecated(classifier) {
+                info!(
+                    "Deprecated classifier '{}' in project {}, replacement: {:?}",
+                    classifier,
+                    project.name,
+                    classifiers::replacement(classifier)
+                );
+                *cr.deprecated.entry(classifier.clone()).or_insert(0) += 1;
+                has_deprecated = true;
+            } else if classifiers::is_unknown(classifier) {
+                info!(
+                    "Unknown classifier '{}' in project {}",
+                    classifier, project.name
+                );
+                *cr.unknown.entry(classifier.clone()).or_insert(0) += 1;
+                has_unknown = true;
+            }
+
+            let (category, value) = classifiers::split_category(classifier);
+            if let Some(counts) = cr.categories.get_mut(category) {
+                *counts.entry(value.to_string()).or_insert(0) += 1;
+            }
+        }
+
+        if has_unknown {
+            cr.unknown_classifiers_count += 1;
+            if cr.unknown_classifiers.len() < PAGE_SIZE {
+                cr.unknown_classifiers.push(project.smaller());
+            }
+        }
+        if has_deprecated {
+            cr.deprecated_classifiers_count += 1;
+            if cr.deprecated_classifiers.len() < PAGE_SIZE {
+                cr.deprecated_classifiers.push(project.smaller());
+            }
+        }
+        if classifiers::is_typed(&project.classifiers) {
+            cr.typed_count += 1;
+        }
+    }
+
+    cr
+}
+
+/// Which files were uploaded for the releases.
+/// Projects saved before we started to collect the files are skipped.
+fn create_distribution_report(projects: &[MyProject]) -> DistributionReport {
+    let mut dr = DistributionReport {
+        kinds: HashMap::new(),
+        pure_python_count: 0,
+        platform_wheels_count: 0,
+        platforms: HashMap::new(),
+        abis: HashMap::new(),
+        no_sdist_count: 0,
+        no_sdist: vec![],
+        no_wheel_count: 0,
+        no_wheel: vec![],
+    };
+
+    for project in projects.iter() {
+        let Some(files) = &project.files else {
+            continue;
+        };
+
+        let kind = distributions::distribution_kind(files);
+        let kind_name = match kind {
+            DistributionKind::SdistOnly => "sdist_only",
+            DistributionKind::WheelOnly => "wheel_only",
+            DistributionKind::Both => "both",
+            DistributionKind::Other => "other",
+        };
+        *dr.kinds.entry(String::from(kind_name)).or_insert(0) += 1;
+
+        if kind != DistributionKind::SdistOnly && kind != DistributionKind::Both {
+            dr.no_sdist_count += 1;
+            if dr.no_sdist.len() < PAGE_SIZE {
+                dr.no_sdist.push(project.smaller());
+            }
+        }
+        if kind != DistributionKind::WheelOnly && kind != DistributionKind::Both {
+            dr.no_wheel_count += 1;
+            if dr.no_wheel.len() < PAGE_SIZE {
+                dr.no_wheel.push(project.smaller());
+            }
+        }
+
+        if files.iter().any(|file| file.is_pure_python_wheel()) {
+            dr.pure_python_count += 1;
+        }
+        if files
+            .iter()
+            .any(|file| file.is_wheel() && !file.is_pure_python_wheel())
+        {
+            dr.platform_wheels_count += 1;
+        }
+        for platform in distributions::platforms(files) {
+            *dr.platforms.entry(platform).or_insert(0) += 1;
+        }
+        for abi in distributions::abis(files) {
+            *dr.abis.entry(abi).or_insert(0) += 1;
+        }
+    }
+
+    dr
+}
+
+/// Which platforms the releases with binary wheels support
+fn create_wheel_report(projects: &[MyProject]) -> WheelReport {
+    let mut wr = WheelReport {
+        binary_releases_count: 0,
+        coverage: HashMap::new(),
+        no_linux_aarch64_count: 0,
+        no_linux_aarch64: vec![],
+        no_macos_arm64_count: 0,
+        no_macos_arm64: vec![],
+        no_windows_count: 0,
+        no_windows: vec![],
+        no_musllinux_count: 0,
+        no_musllinux: vec![],
+    };
+
+    for project in projects.iter() {
+        let Some(coverage) = &project.platform_coverage else {
+            continue;
+        };
+
+        wr.binary_releases_count += 1;
+        for (name, covered) in coverage.entries() {
+            if covered {
+                *wr.coverage.entry(String::from(name)).or_insert(0) += 1;
+            }
+        }
+
+        if !coverage.manylinux_aarch64 && !coverage.musllinux_aarch64 {
+            wr.no_linux_aarch64_count += 1;
+            if wr.no_linux_aarch64.len() < PAGE_SIZE {
+                wr.no_linux_aarch64.push(project.smaller());
+            }
+        }
+        if !coverage.macos_arm64 {
+            wr.no_macos_arm64_count += 1;
+            if wr.no_macos_arm64.len() < PAGE_SIZE {
+                wr.no_macos_arm64.push(project.smaller());
+            }
+        }
+        if !coverage.windows_x86_64 && !coverage.windows_arm64 && !coverage.windows_x86 {
+            wr.no_windows_count += 1;
+            if wr.no_windows.len() < PAGE_SIZE {
+                wr.no_windows.push(project.smaller());
+            }
+        }
+        if !coverage.musllinux_x86_64 && !coverage.musllinux_aarch64 {
+            wr.no_musllinux_count += 1;
+            if wr.no_musllinux.len() < PAGE_SIZE {
+                wr.no_musllinux.push(project.smaller());
+            }
+        }
+    }
+
+    wr
+}
+
+/// New, returning and very active projects based on their release history
+fn create_release_report(projects: &[MyProject]) -> ReleaseReport {
+    let mut rr = ReleaseReport {
+        new_projects_count: 0,
+        new_projects: vec![],
+        dormant_returned_count: 0,
+        dormant_returned: vec![],
+        frequent_releases_count: 0,
+        frequent_releases: vec![],
+        has_yanked_versions_count: 0,
+        has_yanked_versions: vec![],
+    };
+
+    for project in projects.iter() {
+        let Some(stats) = &project.release_stats else {
+            continue;
+        };
+
+        if stats.is_new_project() {
+            rr.new_projects_count += 1;
+            if rr.new_projects.len() < PAGE_SIZE {
+                rr.new_projects.push(project.smaller());
+            }
+        }
+        if stats.is_dormant_returned() {
+            rr.dormant_returned_count += 1;
+            if rr.dormant_returned.len() < PAGE_SIZE {
+                rr.dormant_returned.push(project.smaller());
+            }
+        }
+        if stats.is_frequent() {
+            info!(
+                "Project {} had {} releases in a short time",
+                project.name, stats.recent_release_count
+            );
+            rr.frequent_releases_count += 1;
+            if rr.frequent_releases.len() < PAGE_SIZE {
+                rr.frequent_releases.push(project.smaller());
+            }
+        }
+        if !stats.yanked_versions.is_empty() {
+            rr.has_yanked_versions_count += 1;
+            if rr.has_yanked_versions.len() < PAGE_SIZE {
+                rr.has_yanked_versions.push(project.smaller());
+            }
+        }
+    }
+
+    rr
+}
+
+/// Validate the versions against PEP 440 and classify their scheme
+fn create_version_report(projects: &[MyProject]) -> VersionReport {
+    let mut vr = VersionReport {
+        schemes: HashMap::new(),
+        invalid_count: 0,
+        invalid: vec![],
+        backwards_count: 0,
+        backwards: vec![],
+    };
+
+    for project in projects.iter() {
+        let Some(version) = Version::parse(&project.version) else {
+            info!(
+                "Invalid version '{}' in project {}",
+                project.version, project.name
+            );
+            vr.invalid_count += 1;
+            if vr.invalid.len() < PAGE_SIZE {
+                vr.invalid.push(project.smaller());
+            }
+            continue;
+        };
+
+        for scheme in version.schemes() {
+            *vr.schemes.entry(String::from(scheme)).or_insert(0) += 1;
+        }
+
+        if let Some(previous_version) = &project.previous_version
+            && versions::is_backwards(previous_version, &project.version)
+        {
+            info!(
+                "Version of project {} went backwards from {} to {}",
+                project.name, previous_version, project.version
+            );
+            vr.backwards_count += 1;
+            if vr.backwards.len() < PAGE_SIZE {
+                vr.backwards.push(project.smaller());
+            }
+        }
+    }
+
+    vr
+}
+
+/// Known vulnerabilities of the collected releases and the recently yanked releases of the collected projects
+fn create_vulnerability_report(projects: &[MyProject], now: DateTime<Utc>) -> VulnerabilityReport {
+    let mut vr = VulnerabilityReport {
+        vulnerable_count: 0,
+        vulnerable: vec![],
+        advisories_count: 0,
+        withdrawn_count: 0,
+        yanked_count: 0,
+        yanked: vec![],
+    };
+    let mut advisories: HashSet<String> = HashSet::new();
+    let mut yanked: Vec<YankedRelease> = vec![];
+    let since = now - Duration::days(vulnerabilities::RECENTLY_YANKED_DAYS);
+
+    for project in projects.iter() {
+        vr.withdrawn_count += project
+            .vulnerabilities
+            .iter()
+            .filter(|vulnerability| vulnerability.is_withdrawn())
+            .count() as u32;
+
+        let active = vulnerabilities::active(&project.vulnerabilities);
+        if !active.is_empty() {
+            info!(
+                "Project {} {} has {} known vulnerabilities",
+                project.name,
+                project.version,
+                active.len()
+            );
+            advisories.extend(active.iter().map(|vulnerability| vulnerability.id.clone()));
+            vr.vulnerable_count += 1;
+            if vr.vulnerable.len() < PAGE_SIZE {
+                vr.vulnerable.push(VulnerableProject {
+                    project: project.smaller(),
+                    ids: active
+                        .iter()
+                        .map(|vulnerability| vulnerability.id.clone())
+                        .collect(),
+                    fixed_in: vulnerabilities::fixed_in(&active),
+                });
+            }
+        }
+
+        if project.yanked == Some(true) {
+            yanked.push(YankedRelease {
+                name: project.name.clone(),
+                version: project.version.clone(),
+                reason: project.yanked_reason.clone(),
+                upload_time: Some(project.pub_date),
+            });
+        }
+        if let Some(stats) = &project.release_stats {
+            for version in stats.yanked_versions.iter() {
+                if *version == project.version && project.yanked == Some(true) {
+                    continue;
+                }
+                yanked.push(YankedRelease {
+                    name: project.name.clone(),
+                    version: version.clone(),
+                    reason: stats.yanked_reasons.get(version).cloned(),
+                    upload_time: stats.yanked_upload_times.get(version).copied(),
+                });
+            }
+        }
+    }
+    vr.advisories_count = advisories.len();
+
+    // The releases saved before the upload times were recorded have none, they are not listed
+    yanked.retain(|release| release.upload_time.is_some_and(|time| time >= since));
+    yanked.sort_by(|a, b| {
+        b.upload_time
+            .cmp(&a.upload_time)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    vr.yanked_count = yanked.len() as u32;
+    yanked.truncate(PAGE_SIZE);
+    vr.yanked = yanked;
+
+    vr
+}
+
+/// For each collected project list the other collected projects that depend on it.
+/// Projects nobody depends on are left out.
+fn create_reverse_dependencies(projects: &[MyProject]) -> HashMap<String, Vec<MyFilteredProject>> {
+    let collected: HashMap<String, &str> = projects
+        .iter()
+        .map(|project| (normalize_package_name(&project.name), project.name.as_str()))
+        .collect();
+
+    let mut reverse: HashMap<String, Vec<MyFilteredProject>> = HashMap::new();
+    for project in projects.iter() {
+        let own_name = normalize_package_name(&project.name);
+        for dependency in project.dependency_names() {
+            if dependency == own_name {
+                continue;
+            }
+            if let Some(name) = collected.get(&dependency) {
+                reverse
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(project.smaller());
+            }
+        }
+    }
+
+    reverse
+}
+
+/// Count the projects of each author and maintainer and of each email domain
+fn create_people_report(projects: &[MyProject]) -> PeopleReport {
+    let mut counts: HashMap<String, UploaderCount> = HashMap::new();
+    let mut pr = PeopleReport {
+        distinct_people: 0,
+        top_uploaders: vec![],
+        email_domains: HashMap::new(),
+        no_people_count: 0,
+        no_people: vec![],
+    };
+
+    for project in projects.iter() {
+        let people = project.people();
+        if people.is_empty() {
+            pr.no_people_count += 1;
+            if pr.no_people.len() < PAGE_SIZE {
+                pr.no_people.push(project.smaller());
+            }
+            continue;
+        }
+
+        let mut domains: Vec<&String> = people
+            .iter()
+            .filter_map(|person| person.email_domain.as_ref())
+            .collect();
+        domains.sort();
+        domains.dedup();
+        for domain in domains {
+            *pr.email_domains.entry(domain.clone()).or_insert(0) += 1;
+        }
+
+        for person in people.iter() {
+            counts
+                .entry(person.key())
+                .or_insert_with(|| UploaderCount {
+                    name: person.name.clone(),
+                    email_domain: person.email_domain.clone(),
+                    count: 0,
+                })
+                .count += 1;
+        }
+    }
+
+    pr.distinct_people = counts.len();
+    let mut uploaders: Vec<(String, UploaderCount)> = counts.into_iter().collect();
+    uploaders.sort_by(|a, b| b.1.count.cmp(&a.1.count).then_with(|| a.0.cmp(&b.0)));
+    pr.top_uploaders = uploaders
+        .into_iter()
+        .take(PAGE_SIZE)
+        .map(|(_, uploader)| uploader)
+        .collect();
+
+    pr
+}
+
+/// The projects with names similar to popular packages, the most suspicious first
+fn create_typosquatting_report(projects: &[MyProject]) -> TyposquattingReport {
+    let mut suspicious: Vec<SuspiciousName> = projects
+        .iter()
+        .filter_map(|project| {
+            project
+                .name_suspicion
+                .as_ref()
+                .map(|suspicion| SuspiciousName {
+                    project: project.smaller(),
+                    similar_to: suspicion.similar_to.clone(),
+                    reasons: suspicion.reasons.clone(),
+                    score: suspicion.score,
+                })
+        })
+        .collect();
+    suspicious.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| b.project.pub_date.cmp(&a.project.pub_date))
+    });
+
+    TyposquattingReport {
+        suspicious_count: suspicious.len() as u32,
+        suspicious: suspicious.into_iter().take(PAGE_SIZE).collect(),
+    }
+}
+
+/// Where the projects keep their repository URL and which places disagree
+fn create_repository_report(projects: &[MyProject]) -> RepositoryReport {
+    let mut rr = RepositoryReport {
+        sources: HashMap::new(),
+        conflicting_count: 0,
+        conflicting: vec![],
+        legacy_home_page_count: 0,
+        legacy_home_page: vec![],
+    };
+
+    for project in projects.iter() {
+        if let Some(source) = &project.repository_source {
+            *rr.sources.entry(source.clone()).or_insert(0) += 1;
+            if source == "info.home_page" {
+                rr.legacy_home_page_count += 1;
+                if rr.legacy_home_page.len() < PAGE_SIZE {
+                    rr.legacy_home_page.push(project.smaller());
+                }
+            }
+        }
+        if repository::is_conflicting(&project.repository_candidates) {
+            info!("Project {} has conflicting repository URLs", project.name);
+            rr.conflicting_count += 1;
+            if rr.conflicting.len() < PAGE_SIZE {
+                rr.conflicting.push(ConflictingProject {
+                    project: project.smaller(),
+                    candidates: project.repository_candidates.clone(),
+                });
+            }
+        }
+    }
+
+    rr
+}
+
+/// Do the repositories contain the packages they are linked from
+fn create_provenance_report(projects: &[MyProject]) -> ProvenanceReport {
+    let mut pr = ProvenanceReport {
+        verified_count: 0,
+        unknown_name_count: 0,
+        name_mismatch_count: 0,
+        name_mismatch: vec![],
+        tag_found_count: 0,
+        tag_missing_count: 0,
+        tag_missing: vec![],
+        tag_lag: HashMap::new(),
+    };
+
+    for project in projects.iter() {
+        let Some(provenance) = &project.provenance else {
+            continue;
+        };
+        match provenance.name_matches {
+            Some(true) => pr.verified_count += 1,
+            Some(false) => {
+                pr.name_mismatch_count += 1;
+                if pr.name_mismatch.len() < PAGE_SIZE {
+                    pr.name_mismatch.push(MismatchedProject {
+                        project: project.smaller(),
+                        repository: project.repository_url().unwrap_or_default(),
+                        declared_names: provenance
+                            .declared_names
+                            .iter()
+                            .map(|declared| declared.name.clone())
+                            .collect(),
+                    });
+                }
+            }
+            None => pr.unknown_name_count += 1,
+        }
+        if provenance.is_tag_missing() {
+            pr.tag_missing_count += 1;
+            if pr.tag_missing.len() < PAGE_SIZE {
+                pr.tag_missing.push(project.smaller());
+            }
+        } else if provenance.tag.is_some() {
+            pr.tag_found_count += 1;
+        }
+        if let Some(lag_hours) = provenance.tag_lag_hours {
+            *pr.tag_lag
+                .entry(provenance::tag_lag_bucket(lag_hours).to_string())
+                .or_insert(0) += 1;
+        }
+    }
+
+    pr
+}
+
+/// Which repositories are maintained and which are abandoned
+fn create_activity_report(projects: &[MyProject]) -> ActivityReport {
+    let now = Utc::now();
+    let mut ar = ActivityReport {
+        statuses: HashMap::new(),
+        single_author_count: 0,
+        abandoned_count: 0,
+        abandoned: vec![],
+    };
+
+    let mut abandoned = vec![];
+    for project in projects.iter() {
+        let Some(activity) = &project.activity else {
+            continue;
+        };
+        let status = activity.status(now);
+        *ar.statuses.entry(status.to_string()).or_insert(0) += 1;
+        if activity.authors == 1 {
+            ar.single_author_count += 1;
+        }
+        if status == "abandoned" {
+            abandoned.push((activity.last_commit, project.smaller()));
+        }
+    }
+
+    ar.abandoned_count = abandoned.len() as u32;
+    abandoned.sort_by_key(|(last_commit, _)| *last_commit);
+    ar.abandoned = abandoned
+        .into_iter()
+        .take(PAGE_SIZE)
+        .map(|(_, project)| project)
+        .collect();
+
+    ar
+}
+
+/// Count the projects linking to each host and to each kind of host
+fn create_domain_report(projects: &[MyProject]) -> DomainReport {
+    let mut kinds: HashMap<String, u32> = HashMap::new();
+    let mut hosts: HashMap<String, HostCount> = HashMap::new();
+
+    for project in projects.iter() {
+        let mut project_kinds: HashSet<&String> = HashSet::new();
+        let mut project_hosts: HashSet<&String> = HashSet::new();
+        for url in project.urls.iter() {
+            project_kinds.insert(&url.kind);
+            if let Some(host) = &url.host
+                && project_hosts.insert(host)
+            {
+                hosts
+                    .entry(host.clone())
+                    .or_insert_with(|| HostCount {
+                        host: host.clone(),
+                        kind: url.kind.clone(),
+                        count: 0,
+                    })
+                    .count += 1;
+            }
+        }
+        for kind in project_kinds {
+            *kinds.entry(kind.clone()).or_insert(0) += 1;
+        }
+    }
+
+    let mut popular_hosts: Vec<HostCount> = hosts.into_values().collect();
+    popular_hosts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.host.cmp(&b.host)));
+    popular_hosts.truncate(PAGE_SIZE);
+
+    DomainReport {
+        kinds,
+        popular_hosts,
+    }
+}
+
+/// The projects whose PyPI page will look broken because of the long description, and its size
+fn create_description_report(projects: &[MyProject]) -> DescriptionReport {
+    let mut dr = DescriptionReport {
+        declared_formats: HashMap::new(),
+        detected_formats: HashMap::new(),
+        empty_count: 0,
+        empty: vec![],
+        placeholder_count: 0,
+        placeholder: vec![],
+        format_mismatch_count: 0,
+        format_mismatch: vec![],
+        relative_images_count: 0,
+        relative_images: vec![],
+        sizes: HashMap::new(),
+        huge_count: 0,
+        huge: vec![],
+    };
+    let mut huge: Vec<(usize, &MyProject)> = vec![];
+
+    for project in projects.iter() {
+        if let Some(length) = project.description_length {
+            *dr.sizes
+                .entry(description::size_bucket(length).to_string())
+                .or_insert(0) += 1;
+            if length > description::HUGE_DESCRIPTION {
+                huge.push((length, project));
+            }
+        }
+        let Some(analysis) = &project.description else {
+            continue;
+        };
+        *dr.declared_formats
+            .entry(analysis.declared_format.clone())
+            .or_insert(0) += 1;
+        *dr.detected_formats
+            .entry(analysis.detected_format.clone())
+            .or_insert(0) += 1;
+
+        if analysis.is_empty() {
+            dr.empty_count += 1;
+            if dr.empty.len() < PAGE_SIZE {
+                dr.empty.push(project.smaller());
+            }
+        }
+        if analysis.placeholder {
+            dr.placeholder_count += 1;
+            if dr.placeholder.len() < PAGE_SIZE {
+                dr.placeholder.push(project.smaller());
+            }
+        }
+        if analysis.is_format_mismatch() {
+            info!(
+                "Project {} declares {} but the description looks like {}",
+                project.name, analysis.declared_format, analysis.detected_format
+            );
+            dr.format_mismatch_count += 1;
+            if dr.format_mismatch.len() < PAGE_SIZE {
+                dr.format_mismatch.push(project.smaller());
+            }
+        }
+        if !analysis.relative_images.is_empty() {
+            dr.relative_images_count += 1;
+            if dr.relative_images.len() < PAGE_SIZE {
+                dr.relative_images.push(project.smaller());
+            }
+        }
+    }
+
+    huge.sort_by_key(|(length, _)| std::cmp::Reverse(*length));
+    dr.huge_count = huge.len() as u32;
+    dr.huge = huge
+        .into_iter()
+        .take(PAGE_SIZE)
+        .map(|(_, project)| project.smaller())
+        .collect();
+
+    dr
+}
+
+/// The projects worth a look, the highest risk score first, so they can be triaged daily
+fn create_risk_report(projects: &[MyProject]) -> RiskReport {
+    let mut factors: HashMap<String, u32> = HashMap::new();
+    let mut risky: Vec<RiskyProject> = vec![];
+
+    for project in projects.iter() {
+        for factor in project.risk_factors.iter() {
+            *factors.entry(factor.name.clone()).or_insert(0) += 1;
+        }
+        let score = project.risk_score();
+        if score >= risk::REPORTED_SCORE {
+            risky.push(RiskyProject {
+                project: project.smaller(),
+                score,
+                factors: project.risk_factors.clone(),
+            });
+        }
+    }
+    risky.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| b.project.pub_date.cmp(&a.project.pub_date))
+    });
+
+    RiskReport {
+        factors,
+        worth_a_look_count: risky.len() as u32,
+        worth_a_look: risky.into_iter().take(PAGE_SIZE).collect(),
+    }
+}
+
+/// For each author and maintainer list the collected projects, keyed by Person::key
+fn create_maintainer_index(projects: &[MyProject]) -> HashMap<String, Vec<MyFilteredProject>> {
+    let mut index: HashMap<String, Vec<MyFilteredProject>> = HashMap::new();
+    for project in projects.iter() {
+        for person in project.people() {
+            index
+                .entry(person.key())
+                .or_default()
+                .push(project.smaller());
+        }
+    }
+    index
+}
+
 fn create_urls_report(projects: &[MyProject]) -> HashMap<String, u32> {
     let mut field_counts: HashMap<String, u32> = HashMap::new();
 
@@ -63,9 +1096,13 @@ fn create_urls_report(projects: &[MyProject]) -> HashMap<String, u32> {
 
     field_counts
 }
-fn create_vcs_report(projects: &[MyProject]) -> VCSReport {
+fn create_vcs_report(projects: &[MyProject], vcs_hosts: &VcsHosts) -> VCSReport {
     let mut vr = VCSReport {
         hosts: HashMap::new(),
+        ci_systems: HashMap::new(),
+        clone_outcomes: HashMap::new(),
+        no_ci_count: 0,
+        no_ci: vec![],
         no_vcs_count: 0,
         no_vcs: vec![],
         bad_vcs_count: 0,
@@ -74,18 +1111,10 @@ fn create_vcs_report(projects: &[MyProject]) -> VCSReport {
         github_projects: vec![],
         gitlab_count: 0,
         gitlab_projects: vec![],
-        no_github_actions_count: 0,
-        no_github_actions: vec![],
-        has_github_actions_count: 0,
-        has_github_actions: vec![],
         no_dependabot_count: 0,
         no_dependabot: vec![],
         has_dependabot_count: 0,
         has_dependabot: vec![],
-        has_gitlab_pipeline_count: 0,
-        has_gitlab_pipeline: vec![],
-        no_gitlab_pipeline_count: 0,
-        no_gitlab_pipeline: vec![],
         has_setup_cfg_count: 0,
         has_setup_cfg: vec![],
         no_pyproject_toml_count: 0,
@@ -95,75 +1124,65 @@ fn create_vcs_report(projects: &[MyProject]) -> VCSReport {
     for project in projects.iter() {
         info!("Processing project {} for VCS report", project.name);
 
-        let url = project.repository.clone();
-        if url.is_none() {
+        let Some(url) = &project.repository else {
             vr.no_vcs_count += 1;
             if vr.no_vcs.len() < PAGE_SIZE {
                 vr.no_vcs.push(project.smaller());
             }
             continue;
-        }
-        info!("Checking VCS URL for project {}: {:?}", project.name, url);
-
-        // Here you would add logic to classify the url into known VCS hosts
-        // use Repository from git_digger crate to help with this
-        match url {
-            Some(url) => {
-                let url = url.trim().to_string();
-                match Repository::from_url(&url) {
-                    Ok(repo) => {
-                        if repo.is_github() {
-                            report_github(&mut vr, project);
-                            report_github_action(&mut vr, project);
-                            report_dependabot(&mut vr, project);
-                        } else if repo.is_gitlab() {
-                            report_gitlab(&mut vr, project);
-                            report_gitlab_pipeline(&mut vr, project);
-                        } else {
-                            *vr.hosts.entry(String::from("other")).or_insert(0) += 1;
-                        }
-                        report_project_files(&mut vr, project);
-                    }
-                    Err(_) => {
-                        info!("Unrecognized VCS '{}' in project {}", url, project.name);
-                        vr.bad_vcs_count += 1;
-                        if vr.bad_vcs.len() < PAGE_SIZE {
-                            vr.bad_vcs.push(project.smaller());
-                        }
-                        continue;
-                    }
-                }
+        };
+        info!("Checking VCS URL for project {}: {}", project.name, url);
+
+        // Projects saved before we stored the platform are canonicalized again
+        let platform = match &project.canonical_repository {
+            Some(canonical) if !canonical.platform.is_empty() => Some(canonical.platform.clone()),
+            _ => vcs_hosts
+                .canonicalize(url)
+                .map(|canonical| canonical.platform),
+        };
+        let Some(platform) = platform else {
+            info!("Unrecognized VCS '{}' in project {}", url, project.name);
+            vr.bad_vcs_count += 1;
+            if vr.bad_vcs.len() < PAGE_SIZE {
+                vr.bad_vcs.push(project.smaller());
             }
-            None => {
-                vr.no_vcs_count += 1;
-                if vr.no_vcs.len() < PAGE_SIZE {
-                    vr.no_vcs.push(project.smaller());
-                }
-                continue;
+            continue;
+        };
+
+        *vr.hosts.entry(platform.clone()).or_insert(0) += 1;
+        match platform.as_str() {
+            "github" => {
+                report_github(&mut vr, project);
+                report_dependabot(&mut vr, project);
             }
+            "gitlab" => report_gitlab(&mut vr, project),
+            _ => {}
         }
+        if let Some(outcome) = &project.clone_outcome {
+            *vr.clone_outcomes.entry(outcome.clone()).or_insert(0) += 1;
+        }
+        report_ci_systems(&mut vr, project);
+        report_project_files(&mut vr, project);
     }
     vr
 }
 
-fn report_gitlab_pipeline(vr: &mut VCSReport, project: &MyProject) {
-    if let Some(has_gitlab_pipeline) = project.has_gitlab_pipeline {
-        if has_gitlab_pipeline {
-            vr.has_gitlab_pipeline_count += 1;
-            if vr.has_gitlab_pipeline.len() < PAGE_SIZE {
-                vr.has_gitlab_pipeline.push(project.smaller());
-            }
-        } else {
-            vr.no_gitlab_pipeline_count += 1;
-            if vr.no_gitlab_pipeline.len() < PAGE_SIZE {
-                vr.no_gitlab_pipeline.push(project.smaller());
-            }
+fn report_ci_systems(vr: &mut VCSReport, project: &MyProject) {
+    let Some(ci_systems) = project.ci_systems() else {
+        return;
+    };
+    if ci_systems.is_empty() {
+        vr.no_ci_count += 1;
+        if vr.no_ci.len() < PAGE_SIZE {
+            vr.no_ci.push(project.smaller());
         }
     }
+    for system in ci_systems {
+        *vr.ci_systems.entry(system).or_insert(0) += 1;
+    }
 }
 
 fn report_gitlab(vr: &mut VCSReport, project: &MyProject) {
-    *vr.hosts.entry(String::from("gitlab")).or_insert(0) += 1;
     vr.gitlab_count += 1;
     if vr.gitlab_projects.len() < PAGE_SIZE {
         vr.gitlab_projects.push(project.smaller());
@@ -171,7 +1190,6 @@ fn report_gitlab(vr: &mut VCSReport, project: &MyProject) {
 }
 
 fn report_github(vr: &mut VCSReport, project: &MyProject) {
-    *vr.hosts.entry(String::from("github")).or_insert(0) += 1;
     vr.github_count += 1;
     if vr.github_projects.len() < PAGE_SIZE {
         vr.github_projects.push(project.smaller());
@@ -195,37 +1213,21 @@ fn report_dependabot(vr: &mut VCSReport, project: &MyProject) {
 }
 
 fn report_project_files(vr: &mut VCSReport, project: &MyProject) {
-    if let Some(has_setup_cfg) = project.has_setup_cfg {
-        if has_setup_cfg {
-            vr.has_setup_cfg_count += 1;
-            if vr.has_setup_cfg.len() < PAGE_SIZE {
-                vr.has_setup_cfg.push(project.smaller());
-            }
-        }
-    }
-
-    if let Some(has_pyproject_toml) = project.has_pyproject_toml {
-        if !has_pyproject_toml {
-            vr.no_pyproject_toml_count += 1;
-            if vr.no_pyproject_toml.len() < PAGE_SIZE {
-                vr.no_pyproject_toml.push(project.smaller());
-            }
+    if let Some(has_setup_cfg) = project.has_setup_cfg
+        && has_setup_cfg
+    {
+        vr.has_setup_cfg_count += 1;
+        if vr.has_setup_cfg.len() < PAGE_SIZE {
+            vr.has_setup_cfg.push(project.smaller());
         }
     }
-}
 
-fn report_github_action(vr: &mut VCSReport, project: &MyProject) {
-    if let Some(has_github_actions) = project.has_github_actions {
-        if has_github_actions {
-            vr.has_github_actions_count += 1;
-            if vr.has_github_actions.len() < PAGE_SIZE {
-                vr.has_github_actions.push(project.smaller());
-            }
-        } else {
-            vr.no_github_actions_count += 1;
-            if vr.no_github_actions.len() < PAGE_SIZE {
-                vr.no_github_actions.push(project.smaller());
-            }
+    if let Some(has_pyproject_toml) = project.has_pyproject_toml
+        && !has_pyproject_toml
+    {
+        vr.no_pyproject_toml_count += 1;
+        if vr.no_pyproject_toml.len() < PAGE_SIZE {
+            vr.no_pyproject_toml.push(project.smaller());
         }
     }
 }
@@ -397,7 +1399,7 @@ fn load_all_projects(pypi_dir: &Path) -> Result<Vec<MyProject>, Box<dyn std::err
         }
     }
 
-    projects.sort_by(|a, b| b.pub_date.cmp(&a.pub_date));
+    projects.sort_by_key(|p| std::cmp::Reverse(p.pub_date));
 
     Ok(projects)
 }
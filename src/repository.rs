@@ -0,0 +1,641 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{MyFilteredProject, url_host, urls};
+
+/// The places we look for the repository URL, the most reliable first.
+/// The keys of project_urls are normalized, see
+/// https://packaging.python.org/en/latest/specifications/well-known-project-urls/
+const SOURCES: [&str; 6] = [
+    "project_urls.repository",
+    "project_urls.source",
+    "project_urls.sourcecode",
+    "project_urls.github",
+    "project_urls.homepage",
+    "info.home_page",
+];
+
+/// Sources that are meant to hold the repository, the others only might
+const EXPLICIT_SOURCES: usize = 4;
+
+/// The well known hosts and the platform they run.
+/// Self-hosted GitLab and Gitea/Forgejo instances can be added, see VcsHosts.
+const HOSTS: [(&str, &str); 13] = [
+    ("github.com", "github"),
+    ("gitlab.com", "gitlab"),
+    ("salsa.debian.org", "gitlab"),
+    ("gitlab.gnome.org", "gitlab"),
+    ("invent.kde.org", "gitlab"),
+    ("codeberg.org", "codeberg"),
+    ("bitbucket.org", "bitbucket"),
+    ("git.sr.ht", "sourcehut"),
+    ("hg.sr.ht", "sourcehut"),
+    ("gitea.com", "gitea"),
+    ("notabug.org", "gitea"),
+    ("launchpad.net", "launchpad"),
+    ("git.launchpad.net", "launchpad"),
+];
+
+/// GitHub paths that look like owner/repo but are not repositories
+const RESERVED_OWNERS: [&str; 7] = [
+    "sponsors",
+    "orgs",
+    "settings",
+    "marketplace",
+    "topics",
+    "features",
+    "apps",
+];
+
+/// The pages of a GitLab project in the URLs written before GitLab moved them under `/-/`,
+/// e.g. gitlab.com/group/project/tree/main. The path before them is the project.
+const GITLAB_ROUTES: [&str; 8] = [
+    "tree",
+    "blob",
+    "raw",
+    "issues",
+    "merge_requests",
+    "commits",
+    "tags",
+    "wikis",
+];
+
+/// A URL that might be the repository of the project and where we found it
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct RepositoryCandidate {
+    pub url: String,
+    pub source: String,
+}
+
+/// The repository in a canonical form, e.g. for
+/// `git+https://www.github.com/Pallets/Flask.git@main#subdirectory=src`
+/// the URL is `https://github.com/pallets/flask` the branch is `main` and the subdirectory is `src`
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct CanonicalRepository {
+    /// github, gitlab, codeberg, bitbucket, sourcehut, gitea, launchpad or local
+    #[serde(default)]
+    pub platform: String,
+    pub host: String,
+    /// The groups and subgroups on GitLab, e.g. `python-team/packages`.
+    /// Only the names on GitHub are lower-cased, the other hosts might be case-sensitive.
+    pub owner: String,
+    pub repo: String,
+    /// https://host/owner/repo, or file:///path for local repositories
+    pub url: String,
+    pub branch: Option<String>,
+    pub subdirectory: Option<String>,
+}
+
+/// The hosts we recognize as repository hosts, the built-in ones and the configured
+/// self-hosted GitLab and Gitea/Forgejo instances
+#[derive(Debug, Clone)]
+pub struct VcsHosts {
+    hosts: Vec<(String, &'static str)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConflictingProject {
+    pub project: MyFilteredProject,
+    pub candidates: Vec<RepositoryCandidate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepositoryReport {
+    /// Number of projects taking the repository from each source
+    pub sources: HashMap<String, u32>,
+    pub conflicting_count: u32,
+    pub conflicting: Vec<ConflictingProject>,
+    /// Projects that have the repository only in the legacy home_page field
+    pub legacy_home_page_count: u32,
+    pub legacy_home_page: Vec<MyFilteredProject>,
+}
+
+impl RepositoryCandidate {
+    pub fn new(url: &str, source: &str) -> RepositoryCandidate {
+        RepositoryCandidate {
+            url: url.trim().to_string(),
+            source: source.to_string(),
+        }
+    }
+
+    /// The lower the more reliable
+    pub fn priority(&self) -> usize {
+        source_priority(&self.source)
+    }
+
+    /// Found in a place that is meant to hold the repository or it points to a code hosting site
+    fn is_likely_repository(&self) -> bool {
+        self.priority() < EXPLICIT_SOURCES
+            || url_host(&self.url).is_some_and(|host| {
+                urls::classify(&self.url, Some(host.trim_start_matches("www."))) == "repository"
+            })
+    }
+}
+
+/// The source name of a normalized project_urls key, None if it does not hold a candidate
+pub fn project_urls_source(normalized_key: &str) -> Option<&'static str> {
+    SOURCES
+        .iter()
+        .find(|source| source.strip_prefix("project_urls.") == Some(normalized_key))
+        .copied()
+}
+
+pub fn source_priority(source: &str) -> usize {
+    SOURCES
+        .iter()
+        .position(|known| *known == source)
+        .unwrap_or(SOURCES.len())
+}
+
+/// Sort the candidates by priority, the order of candidates from the same source is kept
+pub fn sort_candidates(candidates: &mut [RepositoryCandidate]) {
+    candidates.sort_by_key(|candidate| candidate.priority());
+}
+
+impl Default for VcsHosts {
+    fn default() -> Self {
+        VcsHosts {
+            hosts: HOSTS
+                .iter()
+                .map(|(host, platform)| (host.to_string(), *platform))
+                .collect(),
+        }
+    }
+}
+
+impl VcsHosts {
+    /// The built-in hosts and the given self-hosted instances
+    pub fn new(gitlab_hosts: &[String], gitea_hosts: &[String]) -> VcsHosts {
+        let mut hosts = VcsHosts::default();
+        for (configured, platform) in [(gitlab_hosts, "gitlab"), (gitea_hosts, "gitea")] {
+            for host in configured {
+                let host = host.trim().to_lowercase();
+                hosts.hosts.retain(|(known, _)| *known != host);
+                hosts.hosts.push((host, platform));
+            }
+        }
+        hosts
+    }
+
+    pub fn platform(&self, host: &str) -> Option<&'static str> {
+        self.hosts
+            .iter()
+            .find(|(known, _)| known == host)
+            .map(|(_, platform)| *platform)
+    }
+
+    /// Turn the many ways people write a repository URL into the canonical form.
+    /// Returns None if the URL is not a repository on one of the known hosts.
+    pub fn canonicalize(&self, url: &str) -> Option<CanonicalRepository> {
+        let url = url.trim();
+        let url = url.strip_prefix("git+").unwrap_or(url);
+
+        let (url, fragment) = match url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment)),
+            None => (url, None),
+        };
+        let url = url.split('?').next().unwrap_or(url);
+
+        // git@github.com:owner/repo.git
+        let rest = if let Some(rest) = url.strip_prefix("git@") {
+            rest.replacen(':', "/", 1)
+        } else {
+            let (_, rest) = url.split_once("://")?;
+            rest.to_string()
+        };
+        let (authority, path) = rest.split_once('/')?;
+        let host = authority.rsplit('@').next()?.to_lowercase();
+        let host = host.split(':').next()?.trim_start_matches("www.");
+        let platform = self.platform(host)?;
+
+        let segments: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+        if platform == "launchpad" {
+            return canonical_launchpad(&segments);
+        }
+        // GitLab projects can be in subgroups: salsa.debian.org/python-team/packages/flask/-/tree/main
+        let repo_end = if platform == "gitlab" {
+            segments
+                .iter()
+                .position(|part| *part == "-" || GITLAB_ROUTES.contains(part))
+                .unwrap_or(segments.len())
+        } else {
+            2
+        };
+        if segments.len() < 2
+            || repo_end < 2
+            || !segments[..repo_end].iter().copied().all(is_plain_name)
+        {
+            return None;
+        }
+        // GitHub names are case-insensitive, other hosts might not be
+        let case = |name: &str| {
+            if platform == "github" {
+                name.to_lowercase()
+            } else {
+                name.to_string()
+            }
+        };
+        let owner = case(&segments[..repo_end - 1].join("/"));
+        if platform == "github" && RESERVED_OWNERS.contains(&owner.as_str()) {
+            return None;
+        }
+        if platform == "sourcehut" && !owner.starts_with('~') {
+            return None;
+        }
+        // pip style pinning: repo.git@v1.0
+        let (repo, mut branch) = match segments[repo_end - 1].split_once('@') {
+            Some((repo, branch)) => (repo, Some(branch.to_string())),
+            None => (segments[repo_end - 1], None),
+        };
+        let repo = case(repo.trim_end_matches(".git"));
+        if !is_plain_name(&repo) {
+            return None;
+        }
+
+        // github.com/o/r/tree/main/docs, gitlab.com/o/r/-/tree/main/docs,
+        // codeberg.org/o/r/src/branch/main/docs, bitbucket.org/o/r/src/main/docs,
+        // git.sr.ht/~o/r/tree/main/item/docs
+        let mut subdirectory = None;
+        let rest: Vec<&str> = segments[repo_end..]
+            .iter()
+            .copied()
+            .skip_while(|part| *part == "-")
+            .collect();
+        let rest = match rest.as_slice() {
+            ["src", "branch", rest @ ..] => rest,
+            ["tree" | "blob" | "src", rest @ ..] => rest,
+            _ => &[],
+        };
+        if let [name, path @ ..] = rest {
+            branch = Some(name.to_string());
+            let path = path.strip_prefix(&["item"]).unwrap_or(path);
+            subdirectory = plain_path(&path.join("/"));
+        }
+        if let Some(fragment) = fragment {
+            for pair in fragment.split('&') {
+                if let Some(path) = pair.strip_prefix("subdirectory=") {
+                    subdirectory = plain_path(path.trim_matches('/'));
+                }
+            }
+        }
+
+        Some(CanonicalRepository {
+            platform: platform.to_string(),
+            url: format!("https://{host}/{owner}/{repo}"),
+            host: host.to_string(),
+            owner,
+            repo,
+            branch,
+            subdirectory,
+        })
+    }
+}
+
+/// A repository on the local disk, e.g. a mirror: file:///srv/git/tool.git or /srv/git/tool.git.
+/// Only the paths given by the operator are accepted, never the URLs in the metadata of a project.
+/// The path is kept as it is, the folders leading to the repository are the owner.
+pub fn canonicalize_local(url: &str) -> Option<CanonicalRepository> {
+    let url = url.trim();
+    let path = match url.strip_prefix("file://") {
+        Some(path) => path,
+        None if url.starts_with('/') => url,
+        None => return None,
+    };
+    let path = path.trim_end_matches('/');
+    let (owner, repo) = path.rsplit_once('/')?;
+    let repo = repo.trim_end_matches(".git");
+    if !path.starts_with('/')
+        || !is_plain_name(repo)
+        || !owner.split('/').skip(1).all(is_plain_name)
+    {
+        return None;
+    }
+    Some(CanonicalRepository {
+        platform: String::from("local"),
+        host: String::from("localhost"),
+        owner: owner.trim_start_matches('/').to_string(),
+        repo: repo.to_string(),
+        url: format!("file://{path}"),
+        branch: None,
+        subdirectory: None,
+    })
+}
+
+/// The name can be a single component of a path: not empty, `.` or `..`, and without a backslash
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains('\\')
+}
+
+/// The relative path, None if it is empty or one of its components is not a plain name
+fn plain_path(path: &str) -> Option<String> {
+    (!path.is_empty() && path.split('/').all(is_plain_name)).then(|| path.to_string())
+}
+
+/// Launchpad projects have a single name: launchpad.net/project or git.launchpad.net/project.
+/// The owner and the repo are both the name of the project.
+fn canonical_launchpad(segments: &[&str]) -> Option<CanonicalRepository> {
+    let project = segments.first()?.trim_end_matches(".git").to_lowercase();
+    if !is_plain_name(&project) || project.starts_with('~') || project.starts_with('+') {
+        return None;
+    }
+    Some(CanonicalRepository {
+        platform: String::from("launchpad"),
+        host: String::from("git.launchpad.net"),
+        owner: project.clone(),
+        url: format!("https://git.launchpad.net/{project}"),
+        repo: project,
+        branch: None,
+        subdirectory: None,
+    })
+}
+
+/// Canonicalize using the built-in hosts only
+pub fn canonicalize(url: &str) -> Option<CanonicalRepository> {
+    VcsHosts::default().canonicalize(url)
+}
+
+/// A form of the URL in which the trivial differences don't count
+fn comparable(url: &str) -> String {
+    if let Some(canonical) = canonicalize(url) {
+        return canonical.url;
+    }
+    url.trim()
+        .to_lowercase()
+        .replacen("http://", "https://", 1)
+        .replacen("://www.", "://", 1)
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .to_string()
+}
+
+/// The candidates that are likely to be repositories point to different places
+pub fn is_conflicting(candidates: &[RepositoryCandidate]) -> bool {
+    let mut urls: Vec<String> = candidates
+        .iter()
+        .filter(|candidate| candidate.is_likely_repository())
+        .map(|candidate| comparable(&candidate.url))
+        .collect();
+    urls.sort();
+    urls.dedup();
+    urls.len() > 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_urls_source() {
+        assert_eq!(
+            project_urls_source("sourcecode"),
+            Some("project_urls.sourcecode")
+        );
+        assert_eq!(
+            project_urls_source("homepage"),
+            Some("project_urls.homepage")
+        );
+        assert_eq!(project_urls_source("documentation"), None);
+        assert_eq!(project_urls_source("home_page"), None);
+    }
+
+    #[test]
+    fn test_sort_candidates() {
+        let mut candidates = vec![
+            RepositoryCandidate::new("https://example.org", "info.home_page"),
+            RepositoryCandidate::new("https://example.org/docs", "project_urls.homepage"),
+            RepositoryCandidate::new("https://github.com/a/b", "project_urls.source"),
+            RepositoryCandidate::new("https://github.com/a/c", "project_urls.repository"),
+        ];
+        sort_candidates(&mut candidates);
+        assert_eq!(
+            candidates
+                .iter()
+                .map(|candidate| candidate.source.as_str())
+                .collect::<Vec<&str>>(),
+            vec![
+                "project_urls.repository",
+                "project_urls.source",
+                "project_urls.homepage",
+                "info.home_page"
+            ]
+        );
+    }
+
+    fn canonical_url(url: &str) -> Option<String> {
+        canonicalize(url).map(|canonical| canonical.url)
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let flask = Some(String::from("https://github.com/pallets/flask"));
+        assert_eq!(canonical_url("https://github.com/pallets/flask"), flask);
+        assert_eq!(canonical_url("https://github.com/pallets/flask/"), flask);
+        assert_eq!(canonical_url("https://github.com/pallets/flask.git"), flask);
+        assert_eq!(canonical_url("http://www.github.com/Pallets/Flask"), flask);
+        assert_eq!(canonical_url("git+https://github.com/pallets/flask"), flask);
+        assert_eq!(canonical_url("git@github.com:pallets/flask.git"), flask);
+        assert_eq!(canonical_url("ssh://git@github.com/pallets/flask"), flask);
+        assert_eq!(
+            canonical_url("https://github.com/pallets/flask/issues"),
+            flask
+        );
+        assert_eq!(
+            canonical_url("https://github.com/pallets/flask#readme"),
+            flask
+        );
+        assert_eq!(
+            canonical_url("https://gitlab.com/group/project/-/tree/main"),
+            Some(String::from("https://gitlab.com/group/project"))
+        );
+
+        assert_eq!(canonical_url("https://github.com/pallets"), None);
+        assert_eq!(canonical_url("https://github.com/sponsors/pallets"), None);
+        assert_eq!(canonical_url("https://flask.palletsprojects.com/"), None);
+        assert_eq!(canonical_url("UNKNOWN"), None);
+    }
+
+    #[test]
+    fn test_canonicalize_branch_and_subdirectory() {
+        let canonical = canonicalize("https://github.com/org/mono/tree/main/packages/lib").unwrap();
+        assert_eq!(canonical.url, "https://github.com/org/mono");
+        assert_eq!(canonical.branch, Some(String::from("main")));
+        assert_eq!(canonical.subdirectory, Some(String::from("packages/lib")));
+
+        let canonical = canonicalize("https://codeberg.org/org/repo/src/branch/dev/sub").unwrap();
+        assert_eq!(canonical.branch, Some(String::from("dev")));
+        assert_eq!(canonical.subdirectory, Some(String::from("sub")));
+
+        let canonical =
+            canonicalize("git+https://github.com/org/mono.git@v1.0#egg=lib&subdirectory=lib/")
+                .unwrap();
+        assert_eq!(canonical.url, "https://github.com/org/mono");
+        assert_eq!(canonical.branch, Some(String::from("v1.0")));
+        assert_eq!(canonical.subdirectory, Some(String::from("lib")));
+
+        let canonical = canonicalize("https://github.com/org/repo").unwrap();
+        assert_eq!(canonical.branch, None);
+        assert_eq!(canonical.subdirectory, None);
+    }
+
+    #[test]
+    fn test_canonicalize_platforms() {
+        let platform = |url: &str| canonicalize(url).map(|canonical| canonical.platform);
+        assert_eq!(
+            platform("https://codeberg.org/forgejo/forgejo"),
+            Some(String::from("codeberg"))
+        );
+        assert_eq!(
+            platform("https://bitbucket.org/owner/repo/src/master/"),
+            Some(String::from("bitbucket"))
+        );
+        assert_eq!(
+            platform("https://salsa.debian.org/python-team/packages/flask"),
+            Some(String::from("gitlab"))
+        );
+        assert_eq!(
+            platform("https://gitea.com/owner/repo"),
+            Some(String::from("gitea"))
+        );
+
+        let canonical =
+            canonicalize("https://git.sr.ht/~sircmpwn/hare/tree/master/item/docs").unwrap();
+        assert_eq!(canonical.platform, "sourcehut");
+        assert_eq!(canonical.url, "https://git.sr.ht/~sircmpwn/hare");
+        assert_eq!(canonical.subdirectory, Some(String::from("docs")));
+        assert_eq!(canonicalize("https://git.sr.ht/sircmpwn/hare"), None);
+
+        let canonical = canonicalize("https://launchpad.net/Beautifulsoup/").unwrap();
+        assert_eq!(canonical.platform, "launchpad");
+        assert_eq!(canonical.url, "https://git.launchpad.net/beautifulsoup");
+        assert_eq!(canonicalize("https://launchpad.net/~someone"), None);
+    }
+
+    #[test]
+    fn test_canonicalize_gitlab_subgroups() {
+        let canonical =
+            canonicalize("https://salsa.debian.org/python-team/packages/flask.git").unwrap();
+        assert_eq!(canonical.owner, "python-team/packages");
+        assert_eq!(canonical.repo, "flask");
+        assert_eq!(
+            canonical.url,
+            "https://salsa.debian.org/python-team/packages/flask"
+        );
+
+        let canonical =
+            canonicalize("https://gitlab.com/Group/Sub/Project/-/tree/main/docs").unwrap();
+        assert_eq!(canonical.url, "https://gitlab.com/Group/Sub/Project");
+        assert_eq!(canonical.branch, Some(String::from("main")));
+        assert_eq!(canonical.subdirectory, Some(String::from("docs")));
+
+        assert_eq!(
+            canonical_url("https://gitlab.com/group/sub/project/issues/12"),
+            Some(String::from("https://gitlab.com/group/sub/project"))
+        );
+        assert_eq!(canonical_url("https://gitlab.com/group/-/issues"), None);
+        assert_eq!(
+            canonical_url("https://codeberg.org/Owner/Repo/src/branch/main"),
+            Some(String::from("https://codeberg.org/Owner/Repo"))
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_local() {
+        let canonical = canonicalize_local("file:///srv/git/Team/Tool.git/").unwrap();
+        assert_eq!(canonical.platform, "local");
+        assert_eq!(canonical.owner, "srv/git/Team");
+        assert_eq!(canonical.repo, "Tool");
+        assert_eq!(canonical.url, "file:///srv/git/Team/Tool.git");
+        assert_eq!(
+            canonicalize_local("/srv/git/tool").map(|canonical| canonical.url),
+            Some(String::from("file:///srv/git/tool"))
+        );
+        assert_eq!(canonicalize_local("file://relative/tool"), None);
+        assert_eq!(canonicalize_local("tool"), None);
+        assert_eq!(canonicalize_local("file:///srv/git/../../etc"), None);
+        assert_eq!(canonicalize_local("/srv//git/tool"), None);
+        assert_eq!(canonicalize_local("/srv/git/.."), None);
+
+        // The URLs in the metadata never point to the local disk
+        assert_eq!(canonicalize("file:///srv/git/tool.git"), None);
+        assert_eq!(canonicalize("git+file:///srv/git/tool.git"), None);
+        assert_eq!(canonicalize("/srv/git/tool.git"), None);
+    }
+
+    #[test]
+    fn test_canonicalize_rejects_dot_components() {
+        assert_eq!(canonicalize("https://github.com/../.."), None);
+        assert_eq!(canonicalize("https://github.com/owner/.."), None);
+        assert_eq!(canonicalize("https://github.com/owner/..git"), None);
+        assert_eq!(canonicalize("https://codeberg.org/./repo"), None);
+        assert_eq!(
+            canonicalize("https://gitlab.com/group/../../project/-/tree/main"),
+            None
+        );
+        assert_eq!(
+            canonical_url("https://github.com/owner/.github"),
+            Some(String::from("https://github.com/owner/.github"))
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_rejects_subdirectory_escapes() {
+        for url in [
+            "git+https://github.com/owner/repo#subdirectory=../../etc",
+            "https://github.com/owner/repo#subdirectory=pkg/./sub",
+            "https://github.com/owner/repo#subdirectory=pkg//sub",
+            "https://github.com/owner/repo#subdirectory=",
+            "https://github.com/owner/repo/tree/main/../../etc",
+            "https://gitlab.com/group/project/-/tree/main/docs/..",
+        ] {
+            let canonical = canonicalize(url).unwrap();
+            assert_eq!(canonical.subdirectory, None, "{url}");
+        }
+        assert_eq!(
+            canonicalize("https://github.com/owner/repo#subdirectory=pkg/sub")
+                .unwrap()
+                .subdirectory,
+            Some(String::from("pkg/sub"))
+        );
+    }
+
+    #[test]
+    fn test_self_hosted() {
+        let url = "https://gitlab.example.org/team/tool/-/tree/main";
+        assert_eq!(canonicalize(url), None);
+
+        let hosts = VcsHosts::new(
+            &[String::from("GitLab.example.org")],
+            &[String::from("git.example.com")],
+        );
+        let canonical = hosts.canonicalize(url).unwrap();
+        assert_eq!(canonical.platform, "gitlab");
+        assert_eq!(canonical.url, "https://gitlab.example.org/team/tool");
+        assert_eq!(
+            hosts
+                .canonicalize("https://git.example.com/team/tool")
+                .map(|canonical| canonical.platform),
+            Some(String::from("gitea"))
+        );
+    }
+
+    #[test]
+    fn test_is_conflicting() {
+        let agreeing = vec![
+            RepositoryCandidate::new("https://github.com/pallets/flask/", "project_urls.source"),
+            RepositoryCandidate::new("http://www.github.com/pallets/flask.git", "info.home_page"),
+            RepositoryCandidate::new(
+                "https://flask.palletsprojects.com/",
+                "project_urls.homepage",
+            ),
+        ];
+        assert!(!is_conflicting(&agreeing));
+
+        let conflicting = vec![
+            RepositoryCandidate::new("https://github.com/pallets/flask", "project_urls.source"),
+            RepositoryCandidate::new("https://gitlab.com/someone/flask", "info.home_page"),
+        ];
+        assert!(is_conflicting(&conflicting));
+        assert!(!is_conflicting(&[]));
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{MyFilteredProject, normalize_package_name};
+
+// See https://packaging.python.org/en/latest/specifications/dependency-specifiers/
+// The longer operators have to come first so "<=" is not taken for "<".
+const OPERATORS: [&str; 8] = ["===", "~=", "==", "!=", "<=", ">=", "<", ">"];
+
+/// The `extra == "name"` clause of a marker, the variable can be on either side
+static EXTRA_MARKER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\bextra\s*==\s*["']|["'][^"']*["']\s*==\s*extra\b"#).unwrap());
+
+/// A single PEP 508 requirement from requires_dist, e.g.
+/// `importlib-metadata>=3.6.0; python_version < "3.10"`
+#[derive(Debug, PartialEq, Eq)]
+pub struct Requirement {
+    /// Normalized package name
+    pub name: String,
+    pub extras: Vec<String>,
+    /// (operator, version) pairs, e.g. (">=", "3.6.0")
+    pub specifiers: Vec<(String, String)>,
+    pub url: Option<String>,
+    pub marker: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RequirementsReport {
+    /// Number of requirements in each category, a requirement can be in more than one
+    pub kinds: HashMap<String, u32>,
+    pub unpinned_count: u32,
+    pub unpinned: Vec<MyFilteredProject>,
+    pub upper_bounded_only_count: u32,
+    pub upper_bounded_only: Vec<MyFilteredProject>,
+    pub exact_pin_count: u32,
+    pub exact_pin: Vec<MyFilteredProject>,
+    pub major_cap_count: u32,
+    pub major_cap: Vec<MyFilteredProject>,
+    pub url_count: u32,
+    pub url: Vec<MyFilteredProject>,
+    pub local_path_count: u32,
+    pub local_path: Vec<MyFilteredProject>,
+}
+
+impl Requirement {
+    pub fn parse(requirement: &str) -> Option<Requirement> {
+        let (spec, marker) = split_marker(requirement);
+        let spec = spec.trim();
+        let name = bare_name(spec)?;
+
+        let mut rest = spec[name.len()..].trim_start();
+        let mut extras = vec![];
+        if let Some(after_bracket) = rest.strip_prefix('[') {
+            let (inside, after) = after_bracket.split_once(']')?;
+            extras = inside
+                .split(',')
+                .map(|extra| extra.trim().to_string())
+                .filter(|extra| !extra.is_empty())
+                .collect();
+            rest = after.trim_start();
+        }
+
+        let mut url = None;
+        let mut specifiers = vec![];
+        if let Some(location) = rest.strip_prefix('@') {
+            url = Some(location.trim().to_string());
+        } else {
+            let rest = rest.trim_start_matches('(').trim_end_matches(')');
+            for clause in rest.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+                let operator = OPERATORS.iter().find(|op| clause.starts_with(*op))?;
+                let version = clause[operator.len()..].trim().to_string();
+                specifiers.push((operator.to_string(), version));
+            }
+        }
+
+        Some(Requirement {
+            name: normalize_package_name(&name),
+            extras,
+            specifiers,
+            url,
+            marker: marker.map(|marker| marker.trim().to_string()),
+        })
+    }
+
+    /// The requirement is only needed when an extra is requested, e.g. `; extra == "dev"`
+    pub fn is_extra_only(&self) -> bool {
+        self.marker
+            .as_ref()
+            .is_some_and(|marker| is_extra_marker(marker))
+    }
+
+    /// No version constraint at all, e.g. `requests`
+    pub fn is_unpinned(&self) -> bool {
+        self.url.is_none() && self.specifiers.is_empty()
+    }
+
+    /// An exact `==` or `===` pin, e.g. `requests==2.31.0` but not `requests==2.*`
+    pub fn is_exact_pin(&self) -> bool {
+        self.specifiers
+            .iter()
+            .any(|(op, version)| op == "===" || (op == "==" && !version.ends_with('*')))
+    }
+
+    /// Only an upper bound without a lower one, e.g. `requests<3`
+    pub fn is_upper_bounded_only(&self) -> bool {
+        let has_upper = self
+            .specifiers
+            .iter()
+            .any(|(op, _)| op == "<" || op == "<=");
+        let has_lower = self
+            .specifiers
+            .iter()
+            .any(|(op, _)| matches!(op.as_str(), ">" | ">=" | "~=" | "==" | "==="));
+        has_upper && !has_lower
+    }
+
+    /// A `<` cap on a major version, e.g. `requests<3` or `requests>=2,<3.0`
+    pub fn has_major_cap(&self) -> bool {
+        self.specifiers.iter().any(|(op, version)| {
+            let mut parts = version.split('.');
+            op == "<"
+                && parts.next().is_some_and(|major| {
+                    !major.is_empty() && major.chars().all(|c| c.is_ascii_digit())
+                })
+                && parts.all(|part| part == "0")
+        })
+    }
+
+    /// Direct reference to a remote URL, e.g. `pkg @ https://example.com/pkg.zip`
+    pub fn is_url(&self) -> bool {
+        self.url.as_ref().is_some_and(|url| !is_local_path(url))
+    }
+
+    /// Direct reference to a local path, e.g. `pkg @ file:///tmp/pkg`
+    pub fn is_local_path(&self) -> bool {
+        self.url.as_ref().is_some_and(|url| is_local_path(url))
+    }
+}
+
+/// The normalized name of a requirement needed to install the project, None for the
+/// requirements of an extra. When the version part cannot be parsed the bare name is
+/// still returned so the dependency is counted.
+pub fn dependency_name(requirement: &str) -> Option<String> {
+    let (spec, marker) = split_marker(requirement);
+    if marker.is_some_and(is_extra_marker) {
+        return None;
+    }
+    bare_name(spec.trim()).map(|name| normalize_package_name(&name))
+}
+
+fn split_marker(requirement: &str) -> (&str, Option<&str>) {
+    match requirement.split_once(';') {
+        Some((spec, marker)) => (spec, Some(marker)),
+        None => (requirement, None),
+    }
+}
+
+/// The name at the beginning of the requirement, not normalized
+fn bare_name(spec: &str) -> Option<String> {
+    let name: String = spec
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_' || *c == '.')
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+fn is_extra_marker(marker: &str) -> bool {
+    EXTRA_MARKER.is_match(marker)
+}
+
+fn is_local_path(url: &str) -> bool {
+    url.starts_with("file:")
+        || url.starts_with("./")
+        || url.starts_with("../")
+        || url.starts_with('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simple() {
+        let req = Requirement::parse("blinker>=1.9.0").unwrap();
+        assert_eq!(req.name, "blinker");
+        assert_eq!(
+            req.specifiers,
+            vec![(String::from(">="), String::from("1.9.0"))]
+        );
+        assert!(req.extras.is_empty());
+        assert_eq!(req.url, None);
+        assert_eq!(req.marker, None);
+    }
+
+    #[test]
+    fn test_parse_marker_extras_and_parens() {
+        let req =
+            Requirement::parse("Requests[socks, security] (>=2.0,<3); extra == \"net\"").unwrap();
+        assert_eq!(req.name, "requests");
+        assert_eq!(req.extras, vec!["socks", "security"]);
+        assert_eq!(
+            req.specifiers,
+            vec![
+                (String::from(">="), String::from("2.0")),
+                (String::from("<"), String::from("3"))
+            ]
+        );
+        assert_eq!(req.marker, Some(String::from("extra == \"net\"")));
+        assert!(req.is_extra_only());
+        assert!(req.has_major_cap());
+        assert!(!req.is_upper_bounded_only());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(Requirement::parse(""), None);
+        assert_eq!(Requirement::parse("foo bar"), None);
+    }
+
+    #[test]
+    fn test_classification() {
+        assert!(Requirement::parse("python-dotenv").unwrap().is_unpinned());
+        assert!(!Requirement::parse("click>=8").unwrap().is_unpinned());
+
+        assert!(Requirement::parse("click==8.1.3").unwrap().is_exact_pin());
+        assert!(!Requirement::parse("click==8.*").unwrap().is_exact_pin());
+
+        assert!(
+            Requirement::parse("click<9")
+                .unwrap()
+                .is_upper_bounded_only()
+        );
+        assert!(
+            !Requirement::parse("click>=8,<9")
+                .unwrap()
+                .is_upper_bounded_only()
+        );
+
+        assert!(Requirement::parse("click<9.0.0").unwrap().has_major_cap());
+        assert!(!Requirement::parse("click<8.2").unwrap().has_major_cap());
+        assert!(!Requirement::parse("click<=9").unwrap().has_major_cap());
+    }
+
+    #[test]
+    fn test_direct_references() {
+        let req = Requirement::parse("pkg @ https://example.com/pkg-1.0.zip").unwrap();
+        assert!(req.is_url());
+        assert!(!req.is_local_path());
+        assert!(!req.is_unpinned());
+
+        let req = Requirement::parse("pkg @ git+https://github.com/org/pkg.git").unwrap();
+        assert!(req.is_url());
+
+        let req = Requirement::parse("pkg @ file:///home/user/pkg").unwrap();
+        assert!(req.is_local_path());
+        assert!(!req.is_url());
+
+        assert!(Requirement::parse("pkg @ ../pkg").unwrap().is_local_path());
+        assert!(
+            Requirement::parse("pkg @ /srv/pkg")
+                .unwrap()
+                .is_local_path()
+        );
+        let req = Requirement::parse("pkg @ ssh-host:pkg.zip").unwrap();
+        assert!(!req.is_local_path());
+        assert!(req.is_url());
+    }
+
+    #[test]
+    fn test_is_extra_only() {
+        let extra_only = |requirement| Requirement::parse(requirement).unwrap().is_extra_only();
+        assert!(extra_only("pytest; extra == 'test'"));
+        assert!(extra_only(
+            "pytest; python_version >= \"3.8\" and extra==\"test\""
+        ));
+        assert!(extra_only("pytest; \"test\" == extra"));
+        assert!(!extra_only("pytest; platform_release == \"extra\""));
+        assert!(!extra_only("extras-tool; python_version < \"3.10\""));
+    }
+
+    #[test]
+    fn test_dependency_name() {
+        assert_eq!(
+            dependency_name("Zope.Interface>=5"),
+            Some(String::from("zope-interface"))
+        );
+        assert_eq!(
+            dependency_name("odd-pkg[broken >=1.0"),
+            Some(String::from("odd-pkg"))
+        );
+        assert_eq!(Requirement::parse("odd-pkg[broken >=1.0"), None);
+        assert_eq!(dependency_name("pytest; extra == \"test\""), None);
+        assert_eq!(dependency_name(""), None);
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::distributions::DistributionKind;
+use crate::{MyFilteredProject, MyProject, distributions, url_host};
+
+/// Projects with at least this score are listed in the report
+pub const REPORTED_SCORE: u32 = 30;
+
+/// Descriptions shorter than this many characters don't tell much about the project
+pub const TINY_DESCRIPTION: usize = 100;
+
+/// Summaries people leave in their templates
+const PLACEHOLDER_SUMMARIES: [&str; 8] = [
+    "unknown",
+    "none",
+    "n/a",
+    "todo",
+    "tbd",
+    "test",
+    "add your description here",
+    "a short description of the project",
+];
+
+/// Hosts that hide where a link leads or that are often used to serve payloads
+const SUSPICIOUS_HOSTS: [&str; 8] = [
+    "bit.ly",
+    "tinyurl.com",
+    "t.co",
+    "goo.gl",
+    "is.gd",
+    "pastebin.com",
+    "transfer.sh",
+    "cdn.discordapp.com",
+];
+
+/// Email providers handing out throw-away addresses
+const DISPOSABLE_EMAIL_DOMAINS: [&str; 7] = [
+    "mailinator.com",
+    "guerrillamail.com",
+    "10minutemail.com",
+    "temp-mail.org",
+    "tempmail.com",
+    "yopmail.com",
+    "sharklasers.com",
+];
+
+/// One signal that makes a project worth a look, with its weight in the score
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct RiskFactor {
+    pub name: String,
+    pub weight: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RiskyProject {
+    pub project: MyFilteredProject,
+    pub score: u32,
+    pub factors: Vec<RiskFactor>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RiskReport {
+    /// Number of projects with each factor
+    pub factors: HashMap<String, u32>,
+    pub worth_a_look_count: u32,
+    /// The projects scoring at least REPORTED_SCORE, the highest score first
+    pub worth_a_look: Vec<RiskyProject>,
+}
+
+impl RiskFactor {
+    fn new(name: &str, weight: u32) -> RiskFactor {
+        RiskFactor {
+            name: name.to_string(),
+            weight,
+        }
+    }
+}
+
+/// Collect the signals from the data we already have about the project.
+/// None of them means the project is malicious, together they help decide what to look at first.
+pub fn risk_factors(project: &MyProject) -> Vec<RiskFactor> {
+    let mut factors = vec![];
+
+    if let Some(suspicion) = &project.name_suspicion {
+        factors.push(RiskFactor::new("similar-name", 30 * suspicion.score / 100));
+    }
+    if is_placeholder_summary(project) {
+        factors.push(RiskFactor::new("placeholder-summary", 10));
+    }
+    if project
+        .description_length
+        .is_some_and(|length| length < TINY_DESCRIPTION)
+    {
+        factors.push(RiskFactor::new("tiny-description", 10));
+    }
+    if project.repository.is_none() {
+        factors.push(RiskFactor::new("no-repository", 15));
+    }
+    if project
+        .provenance
+        .as_ref()
+        .is_some_and(|provenance| provenance.is_name_mismatch())
+    {
+        factors.push(RiskFactor::new("repository-mismatch", 20));
+    }
+    if is_unlicensed(project) {
+        factors.push(RiskFactor::new("no-license", 10));
+    }
+    if let Some(stats) = &project.release_stats {
+        if stats.is_new_project() {
+            factors.push(RiskFactor::new("first-release", 15));
+        }
+        if stats.is_dormant_returned() {
+            factors.push(RiskFactor::new("dormant-returned", 10));
+        }
+    }
+    if project
+        .project_urls
+        .values()
+        .any(|url| is_suspicious_url(url))
+    {
+        factors.push(RiskFactor::new("suspicious-project-urls", 15));
+    }
+    let people = project.people();
+    if people.is_empty() {
+        factors.push(RiskFactor::new("no-author", 5));
+    }
+    if people.iter().any(|person| {
+        person
+            .email_domain
+            .as_ref()
+            .is_some_and(|domain| DISPOSABLE_EMAIL_DOMAINS.contains(&domain.as_str()))
+    }) {
+        factors.push(RiskFactor::new("disposable-email", 20));
+    }
+    if let Some(files) = &project.files
+        && files.len() == 1
+        && distributions::distribution_kind(files) == DistributionKind::SdistOnly
+    {
+        factors.push(RiskFactor::new("single-sdist", 5));
+    }
+
+    factors
+}
+
+fn is_placeholder_summary(project: &MyProject) -> bool {
+    let Some(summary) = &project.summary else {
+        return true;
+    };
+    let summary = summary.trim().trim_end_matches('.').to_lowercase();
+    summary.is_empty()
+        || PLACEHOLDER_SUMMARIES.contains(&summary.as_str())
+        || summary == project.name.to_lowercase()
+}
+
+fn is_unlicensed(project: &MyProject) -> bool {
+    let missing = |license: &Option<String>| {
+        license
+            .as_ref()
+            .is_none_or(|license| license.trim().is_empty() || license == "UNKNOWN")
+    };
+    missing(&project.license) && missing(&project.license_expression)
+}
+
+/// Not http(s), an IP address instead of a host name, or a host from SUSPICIOUS_HOSTS
+fn is_suspicious_url(url: &str) -> bool {
+    if url.trim().is_empty() || url == "UNKNOWN" {
+        return false;
+    }
+    let Some(host) = url_host(url) else {
+        return true;
+    };
+    host.parse::<std::net::IpAddr>().is_ok()
+        || SUSPICIOUS_HOSTS
+            .iter()
+            .any(|suspicious| host == *suspicious || host.ends_with(&format!(".{suspicious}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distributions::DistributionFile;
+    use crate::people::Person;
+    use crate::provenance::Provenance;
+    use crate::releases::{Release, ReleaseStats};
+    use crate::typosquatting::NameSuspicion;
+    use chrono::{DateTime, Utc};
+    use std::path::Path;
+
+    #[test]
+    fn test_is_suspicious_url() {
+        assert!(!is_suspicious_url("https://github.com/pallets/flask"));
+        assert!(!is_suspicious_url("UNKNOWN"));
+        assert!(is_suspicious_url("https://bit.ly/3abc"));
+        assert!(is_suspicious_url("http://192.168.1.10:8000/payload"));
+        assert!(is_suspicious_url("ftp://example.org/file"));
+        assert!(is_suspicious_url(
+            "https://cdn.discordapp.com/attachments/1/2/payload.exe"
+        ));
+        assert!(!is_suspicious_url("https://discord.com/invite/pallets"));
+    }
+
+    /// A project without any risk factor
+    fn project() -> MyProject {
+        serde_json::from_value(serde_json::json!({
+            "name": "flask-tools",
+            "version": "1.2.0",
+            "summary": "Tools for Flask applications",
+            "description_length": 2000,
+            "license_expression": "MIT",
+            "repository": "https://github.com/example/flask-tools",
+            "authors": [{
+                "name": "Alice",
+                "email": "alice@example.org",
+                "email_domain": "example.org"
+            }],
+            "pub_date": 1_700_000_000,
+            "project_urls": {"Source": "https://github.com/example/flask-tools"}
+        }))
+        .unwrap()
+    }
+
+    fn release_stats(versions: &[(&str, &str)]) -> Option<ReleaseStats> {
+        let releases: Vec<Release> = versions
+            .iter()
+            .map(|(version, time)| Release {
+                version: version.to_string(),
+                upload_time: Some(
+                    DateTime::parse_from_rfc3339(time)
+                        .unwrap()
+                        .with_timezone(&Utc),
+                ),
+                yanked: false,
+                yanked_reason: None,
+            })
+            .collect();
+        ReleaseStats::from_releases(&releases, versions.last()?.0)
+    }
+
+    fn names(project: &MyProject) -> Vec<String> {
+        risk_factors(project)
+            .into_iter()
+            .map(|factor| factor.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_risk_factors_none() {
+        assert!(risk_factors(&project()).is_empty());
+    }
+
+    #[test]
+    fn test_risk_factors_one_by_one() {
+        type Change = fn(&mut MyProject);
+        let cases: [(&str, Change); 11] = [
+            ("similar-name", |project| {
+                project.name_suspicion = Some(NameSuspicion {
+                    similar_to: String::from("flask"),
+                    reasons: vec![String::from("affix")],
+                    score: 50,
+                })
+            }),
+            ("placeholder-summary", |project| {
+                project.summary = Some(String::from("Add your description here."))
+            }),
+            ("tiny-description", |project| {
+                project.description_length = Some(20)
+            }),
+            ("no-repository", |project| project.repository = None),
+            ("repository-mismatch", |project| {
+                let mut provenance =
+                    Provenance::check("flask-tools", Path::new("/nonexistent"), None);
+                provenance.name_matches = Some(false);
+                project.provenance = Some(provenance);
+            }),
+            ("no-license", |project| {
+                project.license = Some(String::from("UNKNOWN"));
+                project.license_expression = None;
+            }),
+            ("first-release", |project| {
+                project.release_stats = release_stats(&[("1.2.0", "2024-01-01T00:00:00Z")])
+            }),
+            ("dormant-returned", |project| {
+                project.release_stats = release_stats(&[
+                    ("1.0.0", "2020-01-01T00:00:00Z"),
+                    ("1.2.0", "2024-01-01T00:00:00Z"),
+                ])
+            }),
+            ("suspicious-project-urls", |project| {
+                project.project_urls.insert(
+                    String::from("Download"),
+                    String::from("http://203.0.113.7/flask-tools.zip"),
+                );
+            }),
+            ("disposable-email", |project| {
+                project.maintainers = vec![Person {
+                    name: None,
+                    email: Some(String::from("x@yopmail.com")),
+                    email_domain: Some(String::from("yopmail.com")),
+                }]
+            }),
+            ("single-sdist", |project| {
+                project.files = Some(vec![DistributionFile {
+                    filename: String::from("flask_tools-1.2.0.tar.gz"),
+                    packagetype: Some(String::from("sdist")),
+                    size: Some(1000),
+                    sha256: None,
+                    upload_time: None,
+                    python_version: None,
+                    requires_python: None,
+                    yanked: false,
+                }])
+            }),
+        ];
+        for (name, change) in cases {
+            let mut project = project();
+            change(&mut project);
+            assert_eq!(names(&project), vec![name], "{name}");
+        }
+
+        let mut project = project();
+        project.authors = vec![];
+        assert_eq!(names(&project), vec!["no-author"]);
+    }
+
+    #[test]
+    fn test_similar_name_weight() {
+        let mut project = project();
+        project.name_suspicion = Some(NameSuspicion {
+            similar_to: String::from("flask"),
+            reasons: vec![String::from("homoglyphs")],
+            score: 100,
+        });
+        assert_eq!(
+            risk_factors(&project),
+            vec![RiskFactor::new("similar-name", 30)]
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use crate::normalize_package_name;
+use crate::versions::Version;
+
+/// Prefixes people put before the version in the name of a release tag, e.g. `release-1.2.3`.
+/// A leading `v` is part of PEP 440, `v1.2.3` is parsed as a version.
+const TAG_PREFIXES: [&str; 3] = ["release-", "release/", "release_"];
+
+/// The tag of the release, e.g. `v1.2.3`, `1.2.3`, `release-1.2.3` or `flask-1.2.3`.
+/// The versions are compared as in PEP 440, `v1.2` is the tag of version `1.2.0`.
+pub fn matching_tag(tags: &[String], name: &str, version: &str) -> Option<String> {
+    let version = Version::parse(version)?;
+    let name = normalize_package_name(name);
+    tags.iter()
+        .find(|tag| tag_version(tag, &name).is_some_and(|tagged| tagged == version))
+        .cloned()
+}
+
+/// The version in the name of the tag
+fn tag_version(tag: &str, name: &str) -> Option<Version> {
+    if let Some(version) = Version::parse(tag) {
+        return Some(version);
+    }
+    let lower = tag.to_lowercase();
+    let without_prefix = TAG_PREFIXES
+        .iter()
+        .find_map(|prefix| lower.strip_prefix(prefix))
+        .or_else(|| {
+            // The name of the package, e.g. flask-1.2.3 or flask/v1.2.3
+            let (package, version) = lower.rsplit_once(['-', '/', '@'])?;
+            (normalize_package_name(package) == name).then_some(version)
+        })?;
+    Version::parse(without_prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_matching_tag() {
+        let all = tags(&["0.9", "v1.0.0", "release-1.1", "Flask-2.0.1", "latest"]);
+        assert_eq!(
+            matching_tag(&all, "flask", "1.0"),
+            Some(String::from("v1.0.0"))
+        );
+        assert_eq!(
+            matching_tag(&all, "flask", "1.1.0"),
+            Some(String::from("release-1.1"))
+        );
+        assert_eq!(
+            matching_tag(&all, "flask", "2.0.1"),
+            Some(String::from("Flask-2.0.1"))
+        );
+        assert_eq!(matching_tag(&all, "jinja", "2.0.1"), None);
+        assert_eq!(matching_tag(&all, "flask", "1.2"), None);
+        assert_eq!(matching_tag(&all, "flask", "not a version"), None);
+    }
+}
@@ -0,0 +1,308 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{MyFilteredProject, normalize_package_name};
+
+// The default list of popular package names, one per line.
+// Based on the most downloaded packages on PyPI.
+const POPULAR_PACKAGES: &str = include_str!("popular_packages.txt");
+
+/// Prefixes and suffixes people add to a popular name to make it look official
+const PREFIXES: [&str; 4] = ["python-", "python", "py-", "py"];
+const SUFFIXES: [&str; 8] = ["-python", "python", "-py", "py", "-lib", "-dev", "2", "3"];
+
+const SEPARATORS_SCORE: u32 = 90;
+const HOMOGLYPHS_SCORE: u32 = 90;
+const EDIT_DISTANCE_1_SCORE: u32 = 80;
+const AFFIX_SCORE: u32 = 60;
+const EDIT_DISTANCE_2_SCORE: u32 = 50;
+
+/// The popular names we compare the new names to
+#[derive(Debug)]
+pub struct PopularNames {
+    names: Vec<String>,
+}
+
+/// A name that looks like one of the popular names
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct NameSuspicion {
+    /// The popular name it resembles
+    pub similar_to: String,
+    /// The tricks found: separators, homoglyphs, affix, edit-distance-1, edit-distance-2
+    pub reasons: Vec<String>,
+    /// 0-100, the higher the more suspicious
+    pub score: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SuspiciousName {
+    pub project: MyFilteredProject,
+    pub similar_to: String,
+    pub reasons: Vec<String>,
+    pub score: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TyposquattingReport {
+    pub suspicious_count: u32,
+    /// The most suspicious names first
+    pub suspicious: Vec<SuspiciousName>,
+}
+
+impl Default for PopularNames {
+    fn default() -> Self {
+        PopularNames::from_list(POPULAR_PACKAGES)
+    }
+}
+
+impl PopularNames {
+    /// One name per line, empty lines and lines starting with # are skipped
+    pub fn from_list(list: &str) -> PopularNames {
+        let mut names: Vec<String> = list
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(normalize_package_name)
+            .collect();
+        names.sort();
+        names.dedup();
+        PopularNames { names }
+    }
+
+    pub fn from_file(path: &str) -> Result<PopularNames, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        Ok(PopularNames::from_list(&content))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(&normalize_package_name(name))
+    }
+
+    /// Compare the name to all the popular names and return the most suspicious match.
+    /// The popular names themselves are never suspicious.
+    pub fn check(&self, name: &str) -> Option<NameSuspicion> {
+        let name = normalize_package_name(name);
+        if self.names.contains(&name) {
+            return None;
+        }
+
+        self.names
+            .iter()
+            .filter_map(|popular| compare(&name, popular))
+            .max_by(|a, b| {
+                a.score
+                    .cmp(&b.score)
+                    .then_with(|| b.similar_to.cmp(&a.similar_to))
+            })
+    }
+}
+
+/// Compare two normalized names
+fn compare(name: &str, popular: &str) -> Option<NameSuspicion> {
+    let mut reasons: Vec<(&str, u32)> = vec![];
+
+    let stripped = without_separators(name);
+    let popular_stripped = without_separators(popular);
+    if stripped == popular_stripped {
+        reasons.push(("separators", SEPARATORS_SCORE));
+    } else if skeleton(&stripped) == skeleton(&popular_stripped) {
+        reasons.push(("homoglyphs", HOMOGLYPHS_SCORE));
+    }
+
+    if popular.len() >= 3 && strip_affixes(name).contains(&popular) {
+        reasons.push(("affix", AFFIX_SCORE));
+    }
+
+    if reasons.is_empty() && name.len().abs_diff(popular.len()) <= 2 {
+        match edit_distance(name, popular) {
+            1 if popular.len() >= 4 => reasons.push(("edit-distance-1", EDIT_DISTANCE_1_SCORE)),
+            2 if popular.len() >= 7 => reasons.push(("edit-distance-2", EDIT_DISTANCE_2_SCORE)),
+            _ => {}
+        }
+    }
+
+    let score = reasons.iter().map(|(_, score)| *score).max()?;
+    Some(NameSuspicion {
+        similar_to: popular.to_string(),
+        reasons: reasons
+            .into_iter()
+            .map(|(reason, _)| reason.to_string())
+            .collect(),
+        score,
+    })
+}
+
+fn without_separators(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '-' | '_' | '.'))
+        .collect()
+}
+
+/// Replace the characters that look alike, e.g. `0` and `o` or `rn` and `m`
+fn skeleton(name: &str) -> String {
+    name.replace("rn", "m")
+        .replace("vv", "w")
+        .chars()
+        .map(|c| match c {
+            '0' => 'o',
+            '1' | 'i' => 'l',
+            '3' => 'e',
+            '5' => 's',
+            _ => c,
+        })
+        .collect()
+}
+
+/// The name without each of the known prefixes and suffixes
+fn strip_affixes(name: &str) -> Vec<&str> {
+    let prefixed = PREFIXES
+        .iter()
+        .filter_map(|prefix| name.strip_prefix(prefix));
+    let suffixed = SUFFIXES
+        .iter()
+        .filter_map(|suffix| name.strip_suffix(suffix));
+    prefixed.chain(suffixed).collect()
+}
+
+/// The number of insertions, deletions, substitutions and transpositions of adjacent
+/// characters needed to turn one name into the other (optimal string alignment distance)
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in distances[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(name: &str) -> Option<(String, Vec<String>, u32)> {
+        PopularNames::default()
+            .check(name)
+            .map(|suspicion| (suspicion.similar_to, suspicion.reasons, suspicion.score))
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("requests", "requests"), 0);
+        assert_eq!(edit_distance("requests", "reqeusts"), 1);
+        assert_eq!(edit_distance("requests", "request"), 1);
+        assert_eq!(edit_distance("requests", "requestz"), 1);
+        assert_eq!(edit_distance("django", "djnago"), 1);
+        assert_eq!(edit_distance("flask", "falsk"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_popular_names_are_not_suspicious() {
+        assert_eq!(check("requests"), None);
+        assert_eq!(check("Django"), None);
+        assert_eq!(check("typing_extensions"), None);
+        assert_eq!(check("pytest-cov"), None);
+    }
+
+    #[test]
+    fn test_unrelated_names() {
+        assert_eq!(check("pixelcore"), None);
+        assert_eq!(check("my-company-tools"), None);
+    }
+
+    #[test]
+    fn test_separators() {
+        assert_eq!(
+            check("python_date-util"),
+            Some((
+                String::from("python-dateutil"),
+                vec![String::from("separators")],
+                SEPARATORS_SCORE
+            ))
+        );
+    }
+
+    #[test]
+    fn test_homoglyphs() {
+        assert_eq!(
+            check("reque5ts"),
+            Some((
+                String::from("requests"),
+                vec![String::from("homoglyphs")],
+                HOMOGLYPHS_SCORE
+            ))
+        );
+        assert_eq!(
+            check("nurnpy").map(|(name, _, _)| name),
+            Some(String::from("numpy"))
+        );
+    }
+
+    #[test]
+    fn test_affixes() {
+        assert_eq!(
+            check("requests-python"),
+            Some((
+                String::from("requests"),
+                vec![String::from("affix")],
+                AFFIX_SCORE
+            ))
+        );
+        assert_eq!(
+            check("py-flask").map(|(name, _, _)| name),
+            Some(String::from("flask"))
+        );
+        assert_eq!(
+            check("pandas2").map(|(name, _, _)| name),
+            Some(String::from("pandas"))
+        );
+    }
+
+    #[test]
+    fn test_typos() {
+        assert_eq!(
+            check("reqeusts"),
+            Some((
+                String::from("requests"),
+                vec![String::from("edit-distance-1")],
+                EDIT_DISTANCE_1_SCORE
+            ))
+        );
+        assert_eq!(
+            check("cryptograhpy").map(|(_, reasons, _)| reasons),
+            Some(vec![String::from("edit-distance-1")])
+        );
+        assert_eq!(
+            check("sqlalchemyy").map(|(name, _, _)| name),
+            Some(String::from("sqlalchemy"))
+        );
+    }
+
+    #[test]
+    fn test_custom_list() {
+        let popular = PopularNames::from_list("# our packages\n\nMy_Package\n");
+        assert!(popular.contains("my-package"));
+        assert_eq!(
+            popular.check("my-packag").map(|suspicion| suspicion.score),
+            Some(EDIT_DISTANCE_1_SCORE)
+        );
+        assert_eq!(popular.check("requests"), None);
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{Info, url_host};
+
+static URL_IN_TEXT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"https?://[^\s<>"'`()\[\]{}]+"#).unwrap());
+
+/// The kind of each well known host. A host also matches its subdomains.
+const HOST_KINDS: [(&str, &str); 45] = [
+    ("github.com", "repository"),
+    ("gitlab.com", "repository"),
+    ("bitbucket.org", "repository"),
+    ("codeberg.org", "repository"),
+    ("git.sr.ht", "repository"),
+    ("gitee.com", "repository"),
+    ("sourceforge.net", "repository"),
+    ("launchpad.net", "repository"),
+    ("readthedocs.io", "docs"),
+    ("readthedocs.org", "docs"),
+    ("rtfd.io", "docs"),
+    ("github.io", "docs"),
+    ("gitlab.io", "docs"),
+    ("gitbook.io", "docs"),
+    ("docs.python.org", "docs"),
+    ("img.shields.io", "ci-badge"),
+    ("badge.fury.io", "ci-badge"),
+    ("travis-ci.org", "ci-badge"),
+    ("travis-ci.com", "ci-badge"),
+    ("circleci.com", "ci-badge"),
+    ("ci.appveyor.com", "ci-badge"),
+    ("codecov.io", "ci-badge"),
+    ("coveralls.io", "ci-badge"),
+    ("dev.azure.com", "ci-badge"),
+    ("discord.gg", "chat"),
+    ("discord.com", "chat"),
+    ("gitter.im", "chat"),
+    ("slack.com", "chat"),
+    ("matrix.to", "chat"),
+    ("zulipchat.com", "chat"),
+    ("t.me", "chat"),
+    ("opencollective.com", "funding"),
+    ("patreon.com", "funding"),
+    ("ko-fi.com", "funding"),
+    ("buymeacoffee.com", "funding"),
+    ("liberapay.com", "funding"),
+    ("tidelift.com", "funding"),
+    ("twitter.com", "social"),
+    ("x.com", "social"),
+    ("fosstodon.org", "social"),
+    ("mastodon.social", "social"),
+    ("linkedin.com", "social"),
+    ("youtube.com", "social"),
+    ("pypi.org", "package-index"),
+    ("anaconda.org", "package-index"),
+];
+
+/// A URL found in the metadata or in the description of a project
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct ProjectUrl {
+    pub url: String,
+    /// Where we found it, e.g. `project_urls.Homepage`, `info.docs_url` or `description`
+    pub source: String,
+    pub host: Option<String>,
+    /// repository, docs, ci-badge, chat, funding, social, package-index or other
+    pub kind: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HostCount {
+    pub host: String,
+    pub kind: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DomainReport {
+    /// Number of projects linking to each kind of host
+    pub kinds: HashMap<String, u32>,
+    /// The hosts most projects link to
+    pub popular_hosts: Vec<HostCount>,
+}
+
+impl ProjectUrl {
+    pub fn new(url: &str, source: &str) -> ProjectUrl {
+        let host = url_host(url).map(|host| host.trim_start_matches("www.").to_string());
+        let kind = classify(url, host.as_deref());
+        ProjectUrl {
+            url: url.to_string(),
+            source: source.to_string(),
+            host,
+            kind: kind.to_string(),
+        }
+    }
+}
+
+/// The kind of the URL, based on the host and for some hosts the path
+pub fn classify(url: &str, host: Option<&str>) -> &'static str {
+    let Some(host) = host else {
+        return "other";
+    };
+    let path = url.to_lowercase();
+    if host == "github.com" {
+        if path.contains("github.com/sponsors/") {
+            return "funding";
+        }
+        if path.contains("/actions/workflows/") || path.ends_with("/badge.svg") {
+            return "ci-badge";
+        }
+    }
+    if host == "paypal.me" || (host.ends_with("paypal.com") && path.contains("donate")) {
+        return "funding";
+    }
+    HOST_KINDS
+        .iter()
+        .find(|(known, _)| host == *known || host.ends_with(&format!(".{known}")))
+        .map_or("other", |(_, kind)| kind)
+}
+
+/// The URLs in free text, e.g. in the description
+pub fn urls_in_text(text: &str) -> Vec<String> {
+    URL_IN_TEXT
+        .find_iter(text)
+        .map(|url| {
+            url.as_str()
+                .trim_end_matches(['.', ',', ';', ':', '!', '?', '*', '_'])
+                .to_string()
+        })
+        .collect()
+}
+
+/// All the URLs of the release: the project_urls, the legacy URL fields of the metadata
+/// and the links in the description. Each URL is listed once, with the first place it was found.
+pub fn collect_urls(info: &Info) -> Vec<ProjectUrl> {
+    let mut found: Vec<(String, String)> = vec![];
+    if let Some(project_urls) = &info.project_urls {
+        let mut keys: Vec<&String> = project_urls.keys().collect();
+        keys.sort();
+        for key in keys {
+            if let Some(url) = project_urls[key].as_str() {
+                found.push((url.trim().to_string(), format!("project_urls.{key}")));
+            }
+        }
+    }
+    for (field, value) in [
+        ("info.home_page", &info.home_page),
+        ("info.download_url", &info.download_url),
+        ("info.docs_url", &info.docs_url),
+        ("info.bugtrack_url", &info.bugtrack_url),
+    ] {
+        if let Some(url) = value {
+            found.push((url.trim().to_string(), String::from(field)));
+        }
+    }
+    for url in urls_in_text(&info.description) {
+        found.push((url, String::from("description")));
+    }
+
+    let mut urls: Vec<ProjectUrl> = vec![];
+    for (url, source) in found {
+        if url_host(&url).is_none() || urls.iter().any(|known| known.url == url) {
+            continue;
+        }
+        urls.push(ProjectUrl::new(&url, &source));
+    }
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(url: &str) -> String {
+        ProjectUrl::new(url, "test").kind
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(kind("https://github.com/pallets/flask"), "repository");
+        assert_eq!(kind("https://www.gitlab.com/group/project"), "repository");
+        assert_eq!(kind("https://flask.palletsprojects.com/"), "other");
+        assert_eq!(kind("https://flask.readthedocs.io/en/latest/"), "docs");
+        assert_eq!(kind("https://pallets.github.io/flask"), "docs");
+        assert_eq!(
+            kind("https://github.com/pallets/flask/actions/workflows/tests.yaml/badge.svg"),
+            "ci-badge"
+        );
+        assert_eq!(kind("https://img.shields.io/pypi/v/flask"), "ci-badge");
+        assert_eq!(kind("https://discord.gg/pallets"), "chat");
+        assert_eq!(kind("https://github.com/sponsors/pallets"), "funding");
+        assert_eq!(kind("https://palletsprojects.com/donate"), "other");
+        assert_eq!(kind("https://fosstodon.org/@pallets"), "social");
+        assert_eq!(kind("https://pypi.org/project/flask/"), "package-index");
+        assert_eq!(kind("UNKNOWN"), "other");
+    }
+
+    #[test]
+    fn test_urls_in_text() {
+        assert_eq!(
+            urls_in_text(
+                "See [the docs](https://flask.palletsprojects.com/). Or `this <https://example.org/a>`_, http://example.com."
+            ),
+            vec![
+                "https://flask.palletsprojects.com/",
+                "https://example.org/a",
+                "http://example.com"
+            ]
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::MyFilteredProject;
+
+// The regex from https://packaging.python.org/en/latest/specifications/version-specifiers/#appendix-parsing-version-strings-with-regular-expressions
+static VERSION_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?ix)
+        ^\s*v?
+        (?:(?P<epoch>[0-9]+)!)?
+        (?P<release>[0-9]+(?:\.[0-9]+)*)
+        (?P<pre>
+            [-_.]?
+            (?P<pre_l>alpha|a|beta|b|preview|pre|c|rc)
+            [-_.]?
+            (?P<pre_n>[0-9]+)?
+        )?
+        (?P<post>
+            (?:-(?P<post_n1>[0-9]+))
+            |
+            (?:
+                [-_.]?
+                (?P<post_l>post|rev|r)
+                [-_.]?
+                (?P<post_n2>[0-9]+)?
+            )
+        )?
+        (?P<dev>
+            [-_.]?
+            (?P<dev_l>dev)
+            [-_.]?
+            (?P<dev_n>[0-9]+)?
+        )?
+        (?:\+(?P<local>[a-z0-9]+(?:[-_.][a-z0-9]+)*))?
+        \s*$",
+    )
+    .unwrap()
+});
+
+/// A version as described in PEP 440.
+/// Two versions are equal when they compare equal, e.g. `1.0` and `1.0.0`.
+#[derive(Debug, Clone)]
+pub struct Version {
+    pub epoch: u64,
+    pub release: Vec<u64>,
+    /// The normalized pre-release label (a, b or rc) and number
+    pub pre: Option<(String, u64)>,
+    pub post: Option<u64>,
+    pub dev: Option<u64>,
+    pub local: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionReport {
+    /// Number of projects with each version scheme, a version can have more than one
+    pub schemes: HashMap<String, u32>,
+    pub invalid_count: u32,
+    pub invalid: Vec<MyFilteredProject>,
+    pub backwards_count: u32,
+    pub backwards: Vec<MyFilteredProject>,
+}
+
+impl Version {
+    pub fn parse(version: &str) -> Option<Version> {
+        let caps = VERSION_PATTERN.captures(version)?;
+        let number = |name: &str| -> Option<u64> { caps.name(name)?.as_str().parse().ok() };
+
+        let release = caps["release"]
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<Vec<u64>>>()?;
+
+        let pre = caps.name("pre_l").map(|label| {
+            let label = match label.as_str().to_lowercase().as_str() {
+                "alpha" | "a" => "a",
+                "beta" | "b" => "b",
+                _ => "rc",
+            };
+            (label.to_string(), number("pre_n").unwrap_or(0))
+        });
+        let post = if caps.name("post").is_some() {
+            Some(number("post_n1").or(number("post_n2")).unwrap_or(0))
+        } else {
+            None
+        };
+        let dev = caps.name("dev").map(|_| number("dev_n").unwrap_or(0));
+
+        Some(Version {
+            epoch: number("epoch").unwrap_or(0),
+            release,
+            pre,
+            post,
+            dev,
+            local: caps
+                .name("local")
+                .map(|local| local.as_str().to_lowercase()),
+        })
+    }
+
+    /// Pre-releases and development releases are not final releases
+    pub fn is_pre_release(&self) -> bool {
+        self.pre.is_some() || self.dev.is_some()
+    }
+
+    /// The scheme of the version. A version matches exactly one of
+    /// `calver`, `0.0.x`, `semver` and `other` and then any of
+    /// `pre-release`, `dev`, `post` and `local`.
+    pub fn schemes(&self) -> Vec<&'static str> {
+        let mut schemes = vec![];
+        let first = self.release.first().copied().unwrap_or(0);
+        if (1990..=2100).contains(&first) {
+            schemes.push("calver");
+        } else if self.release.len() >= 3 && self.release[0] == 0 && self.release[1] == 0 {
+            schemes.push("0.0.x");
+        } else if self.release.len() == 3 {
+            schemes.push("semver");
+        } else {
+            schemes.push("other");
+        }
+        if self.pre.is_some() {
+            schemes.push("pre-release");
+        }
+        if self.dev.is_some() {
+            schemes.push("dev");
+        }
+        if self.post.is_some() {
+            schemes.push("post");
+        }
+        if self.local.is_some() {
+            schemes.push("local");
+        }
+        schemes
+    }
+
+    /// The release segment without the trailing zeros, 1.0.0 is the same as 1
+    fn trimmed_release(&self) -> &[u64] {
+        let end = self
+            .release
+            .iter()
+            .rposition(|part| *part != 0)
+            .map_or(0, |index| index + 1);
+        &self.release[..end]
+    }
+
+    /// The key used for ordering the pre, post and dev parts as in PEP 440
+    fn suffix_key(&self) -> (i64, i64, i64, i64) {
+        let (pre_rank, pre_number) = match (&self.pre, self.post, self.dev) {
+            // 1.0.dev1 comes before 1.0a1
+            (None, None, Some(_)) => (-1, 0),
+            (Some((label, number)), _, _) => {
+                let rank = match label.as_str() {
+                    "a" => 0,
+                    "b" => 1,
+                    _ => 2,
+                };
+                (rank, *number as i64)
+            }
+            (None, _, _) => (3, 0),
+        };
+        let post = self.post.map_or(-1, |post| post as i64);
+        let dev = self.dev.map_or(i64::MAX, |dev| dev as i64);
+        (pre_rank, pre_number, post, dev)
+    }
+
+    /// The segments of the local version label, `-` and `_` separate them just like `.`
+    fn local_key(&self) -> Option<Vec<LocalSegment>> {
+        self.local.as_ref().map(|local| {
+            local
+                .split(['.', '-', '_'])
+                .map(|segment| match segment.parse() {
+                    Ok(number) => LocalSegment::Number(number),
+                    Err(_) => LocalSegment::Text(segment.to_lowercase()),
+                })
+                .collect()
+        })
+    }
+}
+
+/// A segment of the local version label. As in PEP 440 the numeric segments compare
+/// as integers and sort above the alphanumeric ones, which compare lexically.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum LocalSegment {
+    Text(String),
+    Number(u64),
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| self.trimmed_release().cmp(other.trimmed_release()))
+            .then_with(|| self.suffix_key().cmp(&other.suffix_key()))
+            .then_with(|| self.local_key().cmp(&other.local_key()))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The new version is lower than the one we stored earlier
+pub fn is_backwards(previous: &str, current: &str) -> bool {
+    match (Version::parse(previous), Version::parse(current)) {
+        (Some(previous), Some(current)) => current < previous,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(version: &str) -> Version {
+        Version::parse(version).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            v("1!2.0.1rc3.post4.dev5+ubuntu.1"),
+            Version {
+                epoch: 1,
+                release: vec![2, 0, 1],
+                pre: Some((String::from("rc"), 3)),
+                post: Some(4),
+                dev: Some(5),
+                local: Some(String::from("ubuntu.1")),
+            }
+        );
+        assert_eq!(v("v1.0").release, vec![1, 0]);
+        assert_eq!(v("1.0-alpha.1").pre, Some((String::from("a"), 1)));
+        assert_eq!(v("0.5beta").pre, Some((String::from("b"), 0)));
+        assert_eq!(v("1.0-1").post, Some(1));
+        assert_eq!(v("1.0.rev2").post, Some(2));
+        assert_eq!(v("1.0.dev").dev, Some(0));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(Version::parse(""), None);
+        assert_eq!(Version::parse("1.2.x"), None);
+        assert_eq!(Version::parse("latest"), None);
+        assert_eq!(Version::parse("1.0 final"), None);
+        assert_eq!(Version::parse("2024-01-01"), None);
+    }
+
+    #[test]
+    fn test_ordering() {
+        let ordered = [
+            "1.0.dev0",
+            "1.0a1.dev1",
+            "1.0a1",
+            "1.0b2",
+            "1.0rc1",
+            "1.0",
+            "1.0+local",
+            "1.0.post1.dev1",
+            "1.0.post1",
+            "1.1",
+            "2.0",
+            "1!0.1",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(v("1.0"), v("1.0.0"));
+        assert_eq!(v("1.0+Ubuntu-1"), v("1.0.0+ubuntu.1"));
+        assert_ne!(v("1.0"), v("1.0+local"));
+    }
+
+    #[test]
+    fn test_local_ordering() {
+        let ordered = [
+            "1.0",
+            "1.0+abc",
+            "1.0+abc.5",
+            "1.0+abc.10",
+            "1.0+abd",
+            "1.0+2",
+            "1.0+10",
+            "1.0+10.a",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(v("1.0+007"), v("1.0+7"));
+    }
+
+    #[test]
+    fn test_schemes() {
+        assert_eq!(v("2024.10.1").schemes(), vec!["calver"]);
+        assert_eq!(v("0.0.5").schemes(), vec!["0.0.x"]);
+        assert_eq!(v("3.1.2").schemes(), vec!["semver"]);
+        assert_eq!(v("1.2").schemes(), vec!["other"]);
+        assert_eq!(v("2.0.0rc1").schemes(), vec!["semver", "pre-release"]);
+        assert_eq!(
+            v("1.0.0.dev3+abc").schemes(),
+            vec!["semver", "dev", "local"]
+        );
+        assert_eq!(v("1.0.0.post1").schemes(), vec!["semver", "post"]);
+    }
+
+    #[test]
+    fn test_is_backwards() {
+        assert!(is_backwards("2.0.0", "1.9.9"));
+        assert!(is_backwards("1.0", "1.0rc1"));
+        assert!(!is_backwards("1.0", "1.0.1"));
+        assert!(!is_backwards("1.0", "1.0.0"));
+        assert!(!is_backwards("latest", "1.0"));
+    }
+}
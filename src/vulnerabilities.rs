@@ -0,0 +1,161 @@
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::MyFilteredProject;
+use crate::versions::Version;
+
+/// Yanked releases uploaded in this many days before the report are listed
+pub const RECENTLY_YANKED_DAYS: i64 = 30;
+
+/// A known vulnerability of a release, from the vulnerabilities array of the PyPI JSON.
+/// See https://docs.pypi.org/api/json/#known-vulnerabilities
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub struct Vulnerability {
+    /// The advisory ID, e.g. `PYSEC-2023-62` or `GHSA-m2qf-hxjv-5gpq`
+    pub id: String,
+    /// Other IDs of the same advisory, e.g. the CVE
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// The versions fixing the vulnerability
+    #[serde(default)]
+    pub fixed_in: Vec<String>,
+    pub link: Option<String>,
+    pub source: Option<String>,
+    /// When the advisory was withdrawn, None if it is still valid
+    pub withdrawn: Option<String>,
+}
+
+/// A yanked release with the reason given by the maintainers
+#[derive(Debug, Serialize)]
+pub struct YankedRelease {
+    pub name: String,
+    pub version: String,
+    pub reason: Option<String>,
+    #[serde(with = "ts_seconds_option")]
+    pub upload_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VulnerableProject {
+    pub project: MyFilteredProject,
+    pub ids: Vec<String>,
+    pub fixed_in: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VulnerabilityReport {
+    pub vulnerable_count: u32,
+    pub vulnerable: Vec<VulnerableProject>,
+    /// Number of distinct advisories that are not withdrawn
+    pub advisories_count: usize,
+    pub withdrawn_count: u32,
+    /// The releases uploaded in the last RECENTLY_YANKED_DAYS and yanked since, the newest first
+    pub yanked_count: u32,
+    pub yanked: Vec<YankedRelease>,
+}
+
+impl Vulnerability {
+    pub fn is_withdrawn(&self) -> bool {
+        self.withdrawn.is_some()
+    }
+}
+
+/// The vulnerabilities that were not withdrawn
+pub fn active(vulnerabilities: &[Vulnerability]) -> Vec<&Vulnerability> {
+    vulnerabilities
+        .iter()
+        .filter(|vulnerability| !vulnerability.is_withdrawn())
+        .collect()
+}
+
+/// The sorted, distinct versions fixing any of the vulnerabilities
+pub fn fixed_in(vulnerabilities: &[&Vulnerability]) -> Vec<String> {
+    let mut versions: Vec<String> = vulnerabilities
+        .iter()
+        .flat_map(|vulnerability| vulnerability.fixed_in.iter().cloned())
+        .collect();
+    // Compared as versions so 10.0 comes after 9.1, the invalid ones come last
+    versions.sort_by(|a, b| match (Version::parse(a), Version::parse(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.cmp(b),
+    });
+    versions.dedup();
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VULNERABILITIES: &str = r#"[
+        {
+            "aliases": ["CVE-2023-30861"],
+            "details": "Flask is a lightweight WSGI web application framework.",
+            "fixed_in": ["2.2.5", "2.3.2"],
+            "id": "PYSEC-2023-62",
+            "link": "https://osv.dev/vulnerability/PYSEC-2023-62",
+            "source": "osv",
+            "summary": null,
+            "withdrawn": null
+        },
+        {
+            "aliases": [],
+            "details": "Withdrawn advisory.",
+            "fixed_in": ["2.3.2"],
+            "id": "GHSA-xxxx-xxxx-xxxx",
+            "link": null,
+            "source": "osv",
+            "summary": null,
+            "withdrawn": "2023-06-01T00:00:00Z"
+        }
+    ]"#;
+
+    #[test]
+    fn test_deserialize() {
+        let vulnerabilities: Vec<Vulnerability> = serde_json::from_str(VULNERABILITIES).unwrap();
+        assert_eq!(
+            vulnerabilities[0],
+            Vulnerability {
+                id: String::from("PYSEC-2023-62"),
+                aliases: vec![String::from("CVE-2023-30861")],
+                fixed_in: vec![String::from("2.2.5"), String::from("2.3.2")],
+                link: Some(String::from("https://osv.dev/vulnerability/PYSEC-2023-62")),
+                source: Some(String::from("osv")),
+                withdrawn: None,
+            }
+        );
+        assert!(!vulnerabilities[0].is_withdrawn());
+        assert!(vulnerabilities[1].is_withdrawn());
+    }
+
+    #[test]
+    fn test_active_and_fixed_in() {
+        let vulnerabilities: Vec<Vulnerability> = serde_json::from_str(VULNERABILITIES).unwrap();
+        let active = active(&vulnerabilities);
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].id, "PYSEC-2023-62");
+        assert_eq!(fixed_in(&active), vec!["2.2.5", "2.3.2"]);
+        assert_eq!(fixed_in(&[]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_fixed_in_sorts_versions() {
+        let vulnerability = |fixed_in: &[&str]| Vulnerability {
+            id: String::from("PYSEC-0000-1"),
+            aliases: vec![],
+            fixed_in: fixed_in.iter().map(|version| version.to_string()).collect(),
+            link: None,
+            source: None,
+            withdrawn: None,
+        };
+        let first = vulnerability(&["10.0", "9.1"]);
+        let second = vulnerability(&["not-a-version", "9.1", "1.10.2"]);
+        assert_eq!(
+            fixed_in(&[&first, &second]),
+            vec!["1.10.2", "9.1", "10.0", "not-a-version"]
+        );
+    }
+}
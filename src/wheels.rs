@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::MyFilteredProject;
+use crate::distributions::DistributionFile;
+
+/// The tags of a wheel filename, see
+/// https://packaging.python.org/en/latest/specifications/binary-distribution-format/
+/// Compressed tag sets such as `manylinux_2_17_x86_64.manylinux2014_x86_64` are expanded.
+#[derive(Debug, PartialEq, Eq)]
+pub struct WheelTags {
+    pub python: Vec<String>,
+    pub abi: Vec<String>,
+    pub platform: Vec<String>,
+}
+
+/// Which of the common platforms the binary wheels of a release cover
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Default)]
+pub struct PlatformCoverage {
+    pub manylinux_x86_64: bool,
+    pub manylinux_aarch64: bool,
+    pub musllinux_x86_64: bool,
+    pub musllinux_aarch64: bool,
+    pub macos_x86_64: bool,
+    pub macos_arm64: bool,
+    pub windows_x86_64: bool,
+    pub windows_arm64: bool,
+    pub windows_x86: bool,
+    pub abi3: bool,
+    pub free_threaded: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WheelReport {
+    /// Number of releases with at least one platform specific wheel
+    pub binary_releases_count: u32,
+    /// Number of binary releases covering each platform
+    pub coverage: HashMap<String, u32>,
+    pub no_linux_aarch64_count: u32,
+    pub no_linux_aarch64: Vec<MyFilteredProject>,
+    pub no_macos_arm64_count: u32,
+    pub no_macos_arm64: Vec<MyFilteredProject>,
+    pub no_windows_count: u32,
+    pub no_windows: Vec<MyFilteredProject>,
+    pub no_musllinux_count: u32,
+    pub no_musllinux: Vec<MyFilteredProject>,
+}
+
+impl WheelTags {
+    pub fn parse(filename: &str) -> Option<WheelTags> {
+        let stem = filename.strip_suffix(".whl")?;
+        let parts: Vec<&str> = stem.split('-').collect();
+        // name-version(-build)-python-abi-platform
+        if parts.len() != 5 && parts.len() != 6 {
+            return None;
+        }
+        let n = parts.len();
+        let expand = |tag: &str| tag.split('.').map(String::from).collect::<Vec<String>>();
+        Some(WheelTags {
+            python: expand(parts[n - 3]),
+            abi: expand(parts[n - 2]),
+            platform: expand(parts[n - 1]),
+        })
+    }
+
+    /// A wheel that works everywhere, tagged `none-any`
+    pub fn is_pure_python(&self) -> bool {
+        self.abi.iter().all(|abi| abi == "none") && self.platform.iter().all(|p| p == "any")
+    }
+
+    /// Built for the free-threaded build of CPython, e.g. `cp313t`
+    pub fn is_free_threaded(&self) -> bool {
+        self.abi
+            .iter()
+            .any(|abi| abi.starts_with("cp") && abi.ends_with('t'))
+    }
+}
+
+impl PlatformCoverage {
+    /// The coverage of all the platform specific wheels, None if there are none
+    pub fn from_files(files: &[DistributionFile]) -> Option<PlatformCoverage> {
+        let mut coverage = PlatformCoverage::default();
+        let mut binary = false;
+        for tags in files.iter().filter_map(|file| file.wheel_tags()) {
+            if tags.is_pure_python() {
+                continue;
+            }
+            binary = true;
+            coverage.abi3 |= tags.abi.iter().any(|abi| abi == "abi3");
+            coverage.free_threaded |= tags.is_free_threaded();
+            for platform in tags.platform.iter() {
+                coverage.add_platform(platform);
+            }
+        }
+        binary.then_some(coverage)
+    }
+
+    fn add_platform(&mut self, platform: &str) {
+        let arch = |suffix: &str| platform.ends_with(suffix);
+        if platform.starts_with("manylinux") {
+            self.manylinux_x86_64 |= arch("_x86_64");
+            self.manylinux_aarch64 |= arch("_aarch64");
+        } else if platform.starts_with("musllinux") {
+            self.musllinux_x86_64 |= arch("_x86_64");
+            self.musllinux_aarch64 |= arch("_aarch64");
+        } else if platform.starts_with("macosx") {
+            self.macos_x86_64 |= arch("_x86_64") || arch("_universal2") || arch("_intel");
+            self.macos_arm64 |= arch("_arm64") || arch("_universal2");
+        } else if platform == "win_amd64" {
+            self.windows_x86_64 = true;
+        } else if platform == "win_arm64" {
+            self.windows_arm64 = true;
+        } else if platform == "win32" {
+            self.windows_x86 = true;
+        }
+    }
+
+    /// The coverage as (name, covered) pairs, used to aggregate it in the report
+    pub fn entries(&self) -> [(&'static str, bool); 11] {
+        [
+            ("manylinux_x86_64", self.manylinux_x86_64),
+            ("manylinux_aarch64", self.manylinux_aarch64),
+            ("musllinux_x86_64", self.musllinux_x86_64),
+            ("musllinux_aarch64", self.musllinux_aarch64),
+            ("macos_x86_64", self.macos_x86_64),
+            ("macos_arm64", self.macos_arm64),
+            ("windows_x86_64", self.windows_x86_64),
+            ("windows_arm64", self.windows_arm64),
+            ("windows_x86", self.windows_x86),
+            ("abi3", self.abi3),
+            ("free_threaded", self.free_threaded),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wheel(filename: &str) -> DistributionFile {
+        DistributionFile {
+            filename: String::from(filename),
+            packagetype: Some(String::from("bdist_wheel")),
+            size: None,
+            sha256: None,
+            upload_time: None,
+            python_version: None,
+            requires_python: None,
+            yanked: false,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            WheelTags::parse("flask-3.1.2-py3-none-any.whl"),
+            Some(WheelTags {
+                python: vec![String::from("py3")],
+                abi: vec![String::from("none")],
+                platform: vec![String::from("any")],
+            })
+        );
+        assert_eq!(
+            WheelTags::parse(
+                "numpy-2.1.0-1-cp312-cp312-manylinux_2_17_x86_64.manylinux2014_x86_64.whl"
+            ),
+            Some(WheelTags {
+                python: vec![String::from("cp312")],
+                abi: vec![String::from("cp312")],
+                platform: vec![
+                    String::from("manylinux_2_17_x86_64"),
+                    String::from("manylinux2014_x86_64")
+                ],
+            })
+        );
+        assert_eq!(
+            WheelTags::parse("six-1.16.0-py2.py3-none-any.whl").map(|tags| tags.python),
+            Some(vec![String::from("py2"), String::from("py3")])
+        );
+        assert_eq!(WheelTags::parse("flask-3.1.2.tar.gz"), None);
+        assert_eq!(WheelTags::parse("bad-name.whl"), None);
+    }
+
+    #[test]
+    fn test_free_threaded() {
+        let tags = WheelTags::parse("pkg-1.0-cp313-cp313t-win_amd64.whl").unwrap();
+        assert!(tags.is_free_threaded());
+        let tags = WheelTags::parse("pkg-1.0-cp313-cp313-win_amd64.whl").unwrap();
+        assert!(!tags.is_free_threaded());
+    }
+
+    #[test]
+    fn test_coverage() {
+        assert_eq!(
+            PlatformCoverage::from_files(&[wheel("pkg-1.0-py3-none-any.whl")]),
+            None
+        );
+
+        let coverage = PlatformCoverage::from_files(&[
+            wheel("pkg-1.0-cp39-abi3-manylinux_2_17_x86_64.manylinux2014_x86_64.whl"),
+            wheel("pkg-1.0-cp39-abi3-musllinux_1_2_aarch64.whl"),
+            wheel("pkg-1.0-cp39-abi3-macosx_10_12_universal2.whl"),
+            wheel("pkg-1.0-cp313-cp313t-win_amd64.whl"),
+        ])
+        .unwrap();
+        assert_eq!(
+            coverage,
+            PlatformCoverage {
+                manylinux_x86_64: true,
+                manylinux_aarch64: false,
+                musllinux_x86_64: false,
+                musllinux_aarch64: true,
+                macos_x86_64: true,
+                macos_arm64: true,
+                windows_x86_64: true,
+                windows_arm64: false,
+                windows_x86: false,
+                abi3: true,
+                free_threaded: true,
+            }
+        );
+    }
+}